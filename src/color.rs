@@ -1,5 +1,8 @@
 //! Helper methods for colors
 
+use std::error::Error;
+use std::fmt;
+
 use types::{Color, ColorComponent};
 
 /// White color.
@@ -9,6 +12,46 @@ pub const BLACK: Color = [0.0, 0.0, 0.0, 1.0];
 /// Transparent color.
 pub const TRANSPARENT: Color = [0.0; 4];
 
+/// Named colors from the CSS basic palette.
+pub mod named {
+    use types::Color;
+
+    /// Black color.
+    pub const BLACK: Color = [0.0, 0.0, 0.0, 1.0];
+    /// Silver color.
+    pub const SILVER: Color = [0.7529412, 0.7529412, 0.7529412, 1.0];
+    /// Gray color.
+    pub const GRAY: Color = [0.5019608, 0.5019608, 0.5019608, 1.0];
+    /// White color.
+    pub const WHITE: Color = [1.0, 1.0, 1.0, 1.0];
+    /// Maroon color.
+    pub const MAROON: Color = [0.5019608, 0.0, 0.0, 1.0];
+    /// Red color.
+    pub const RED: Color = [1.0, 0.0, 0.0, 1.0];
+    /// Purple color.
+    pub const PURPLE: Color = [0.5019608, 0.0, 0.5019608, 1.0];
+    /// Fuchsia color.
+    pub const FUCHSIA: Color = [1.0, 0.0, 1.0, 1.0];
+    /// Green color.
+    pub const GREEN: Color = [0.0, 0.5019608, 0.0, 1.0];
+    /// Lime color.
+    pub const LIME: Color = [0.0, 1.0, 0.0, 1.0];
+    /// Olive color.
+    pub const OLIVE: Color = [0.5019608, 0.5019608, 0.0, 1.0];
+    /// Yellow color.
+    pub const YELLOW: Color = [1.0, 1.0, 0.0, 1.0];
+    /// Navy color.
+    pub const NAVY: Color = [0.0, 0.0, 0.5019608, 1.0];
+    /// Blue color.
+    pub const BLUE: Color = [0.0, 0.0, 1.0, 1.0];
+    /// Teal color.
+    pub const TEAL: Color = [0.0, 0.5019608, 0.5019608, 1.0];
+    /// Aqua color.
+    pub const AQUA: Color = [0.0, 1.0, 1.0, 1.0];
+    /// Transparent color.
+    pub const TRANSPARENT: Color = [0.0; 4];
+}
+
 /// Returns a grey color
 pub fn grey(f: ColorComponent) -> Color {
     [f, f, f, 1.0]
@@ -19,20 +62,277 @@ pub fn alpha(f: ColorComponent) -> Color {
     [1.0, 1.0, 1.0, f]
 }
 
-/// Converts from hexadecimal color format
+/// Implemented by values that can be converted to a color.
+///
+/// This is used instead of `Into<Color>`,
+/// because `From` can not be implemented between arrays.
+pub trait IntoColor {
+    /// Converts to a color.
+    fn into_color(self) -> Color;
+}
+
+impl IntoColor for Color {
+    #[inline(always)]
+    fn into_color(self) -> Color {
+        self
+    }
+}
+
+/// Red, green and blue with opaque alpha.
+impl IntoColor for [ColorComponent; 3] {
+    #[inline(always)]
+    fn into_color(self) -> Color {
+        [self[0], self[1], self[2], 1.0]
+    }
+}
+
+/// Red, green, blue and alpha from 0 to 255.
+impl IntoColor for [u8; 4] {
+    #[inline(always)]
+    fn into_color(self) -> Color {
+        [self[0] as ColorComponent / 255.0,
+         self[1] as ColorComponent / 255.0,
+         self[2] as ColorComponent / 255.0,
+         self[3] as ColorComponent / 255.0]
+    }
+}
+
+/// An opaque grey color, see `grey`.
+impl IntoColor for ColorComponent {
+    #[inline(always)]
+    fn into_color(self) -> Color {
+        grey(self)
+    }
+}
+
+/// Converts from hexadecimal color format.
+///
+/// Panics if the string is not a valid hexadecimal color.
+/// See `color_from_hex` for the supported formats.
 pub fn hex(hex: &str) -> Color {
-    use read_color::rgb_maybe_a;
+    color_from_hex(hex).unwrap()
+}
 
-    let (rgb, a) = rgb_maybe_a(&mut hex.chars()).unwrap();
-    let color = match a {
-        None => [rgb[0], rgb[1], rgb[2], 255],
-        Some(a) => [rgb[0], rgb[1], rgb[2], a],
+/// An error when parsing a hexadecimal color.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid hexadecimal color")
+    }
+}
+
+impl Error for ParseColorError {}
+
+/// Parses a color from hexadecimal color format.
+///
+/// Supports 3 (RGB), 4 (RGBA), 6 (RRGGBB) and 8 (RRGGBBAA) digits,
+/// with or without a leading `#`.
+/// Alpha is 1.0 when it is left out.
+pub fn color_from_hex(hex: &str) -> Result<Color, ParseColorError> {
+    use read_color::char_to_hex;
+
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    let mut digits = [0; 8];
+    let mut n = 0;
+    for ch in hex.chars() {
+        if n == digits.len() {
+            return Err(ParseColorError);
+        }
+        digits[n] = match char_to_hex(ch) {
+            None => return Err(ParseColorError),
+            Some(x) => x,
+        };
+        n += 1;
+    }
+    let color = match n {
+        3 | 4 => {
+            let a = if n == 4 { digits[3] * 17 } else { 255 };
+            [digits[0] * 17, digits[1] * 17, digits[2] * 17, a]
+        }
+        6 | 8 => {
+            let a = if n == 8 { (digits[6] << 4) | digits[7] } else { 255 };
+            [(digits[0] << 4) | digits[1],
+             (digits[2] << 4) | digits[3],
+             (digits[4] << 4) | digits[5],
+             a]
+        }
+        _ => return Err(ParseColorError),
     };
     let inv_255 = 1.0f32 / 255.0f32;
-    [color[0] as f32 * inv_255,
-     color[1] as f32 * inv_255,
-     color[2] as f32 * inv_255,
-     color[3] as f32 * inv_255]
+    Ok([color[0] as f32 * inv_255,
+        color[1] as f32 * inv_255,
+        color[2] as f32 * inv_255,
+        color[3] as f32 * inv_255])
+}
+
+/// Converts from hue, saturation and lightness to a color.
+///
+/// Hue is in degrees and wraps around at 360.
+/// Saturation and lightness are between 0.0 and 1.0.
+pub fn hsl_to_rgba(h: ColorComponent,
+                   s: ColorComponent,
+                   l: ColorComponent,
+                   a: ColorComponent)
+                   -> Color {
+    let h = (h % 360.0 + 360.0) % 360.0 / 60.0;
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = l - 0.5 * c;
+    let (r, g, b) = match h as u8 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m, a]
+}
+
+/// Converts from a color to `[hue, saturation, lightness, alpha]`.
+///
+/// Hue is in degrees between 0.0 and 360.0.
+/// Grey colors have hue and saturation 0.0.
+pub fn rgba_to_hsl(color: Color) -> [ColorComponent; 4] {
+    let (r, g, b) = (color[0], color[1], color[2]);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = 0.5 * (max + min);
+    if max == min {
+        return [0.0, 0.0, l, color[3]];
+    }
+
+    let d = max - min;
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        ((g - b) / d + 6.0) % 6.0
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    [h * 60.0, s, l, color[3]]
+}
+
+/// Converts from hue, saturation and value to a color.
+///
+/// Hue is in degrees and wraps around at 360.
+/// Saturation and value are between 0.0 and 1.0.
+pub fn hsv_to_rgba(h: ColorComponent,
+                   s: ColorComponent,
+                   v: ColorComponent,
+                   a: ColorComponent)
+                   -> Color {
+    let h = (h % 360.0 + 360.0) % 360.0 / 60.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = v - c;
+    let (r, g, b) = match h as u8 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m, a]
+}
+
+/// Converts from a color to `[hue, saturation, value, alpha]`.
+///
+/// Hue is in degrees between 0.0 and 360.0.
+/// Grey colors have hue and saturation 0.0.
+pub fn rgba_to_hsv(color: Color) -> [ColorComponent; 4] {
+    let (r, g, b) = (color[0], color[1], color[2]);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    if max == min {
+        return [0.0, 0.0, max, color[3]];
+    }
+
+    let d = max - min;
+    let h = if max == r {
+        ((g - b) / d + 6.0) % 6.0
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    [h * 60.0, d / max, max, color[3]]
+}
+
+/// How alpha is stored in a color.
+///
+/// Colors are plain arrays and do not remember their alpha mode,
+/// so methods that depend on it take the mode as an argument.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AlphaMode {
+    /// Red, green and blue are independent of alpha.
+    Straight,
+    /// Red, green and blue are already multiplied with alpha.
+    Premultiplied,
+}
+
+/// Blend modes for combining two colors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Uses the source color.
+    Normal,
+    /// Multiplies the colors, which darkens.
+    Multiply,
+    /// Inverts, multiplies and inverts the colors, which lightens.
+    Screen,
+    /// Multiplies dark destination colors and screens light destination colors.
+    Overlay,
+    /// Adds the colors, clamped to 1.0.
+    Add,
+    /// Subtracts the source from the destination, clamped to 0.0.
+    Subtract,
+}
+
+impl BlendMode {
+    #[inline(always)]
+    fn component(self, cb: ColorComponent, cs: ColorComponent) -> ColorComponent {
+        match self {
+            BlendMode::Normal => cs,
+            BlendMode::Multiply => cb * cs,
+            BlendMode::Screen => cb + cs - cb * cs,
+            BlendMode::Overlay => {
+                if cb <= 0.5 {
+                    2.0 * cb * cs
+                } else {
+                    1.0 - 2.0 * (1.0 - cb) * (1.0 - cs)
+                }
+            }
+            BlendMode::Add => (cb + cs).min(1.0),
+            BlendMode::Subtract => (cb - cs).max(0.0),
+        }
+    }
+}
+
+/// Blends a source color over a destination color with a blend mode.
+///
+/// Colors use straight (not premultiplied) alpha.
+/// The blended color is weighted by the overlap of source and destination alpha,
+/// and composited with source over destination.
+/// Returns transparent black when both colors are fully transparent.
+pub fn blend(dst: Color, src: Color, mode: BlendMode) -> Color {
+    let (ab, as_) = (dst[3], src[3]);
+    let ao = as_ + ab * (1.0 - as_);
+    if ao == 0.0 {
+        return TRANSPARENT;
+    }
+
+    let mut res = [0.0, 0.0, 0.0, ao];
+    for i in 0..3 {
+        let (cb, cs) = (dst[i], src[i]);
+        res[i] = ((1.0 - ab) * as_ * cs +
+                  as_ * ab * mode.component(cb, cs) +
+                  (1.0 - as_) * ab * cb) / ao;
+    }
+    res
 }
 
 #[inline(always)]
@@ -74,3 +374,95 @@ pub fn gamma_linear_to_srgb(c: Color) -> Color {
      component_linear_to_srgb(c[2]),
      c[3]]
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(a: [ColorComponent; 4], b: [ColorComponent; 4]) {
+        for i in 0..4 {
+            assert!((a[i] - b[i]).abs() < 0.00001, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_color_from_hex() {
+        assert_eq!(color_from_hex("#ff0000"), Ok([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(color_from_hex("00ff0000"), Ok([0.0, 1.0, 0.0, 0.0]));
+        assert_eq!(color_from_hex("#f00"), Ok([1.0, 0.0, 0.0, 1.0]));
+        assert_eq!(color_from_hex("0f0f"), Ok([0.0, 1.0, 0.0, 1.0]));
+        assert_eq!(color_from_hex("#ff8800"), color_from_hex("f80"));
+        assert_eq!(color_from_hex(""), Err(ParseColorError));
+        assert_eq!(color_from_hex("#ff00"), Ok([1.0, 1.0, 0.0, 0.0]));
+        assert_eq!(color_from_hex("ff000"), Err(ParseColorError));
+        assert_eq!(color_from_hex("ff00001234"), Err(ParseColorError));
+        assert_eq!(color_from_hex("gg0000"), Err(ParseColorError));
+        assert_eq!(hex("ffffff"), WHITE);
+    }
+
+    #[test]
+    fn test_hsv() {
+        assert_near(rgba_to_hsv([1.0, 0.0, 0.0, 1.0]), [0.0, 1.0, 1.0, 1.0]);
+        assert_near(rgba_to_hsv([0.0, 0.5, 0.0, 0.5]), [120.0, 1.0, 0.5, 0.5]);
+        assert_near(rgba_to_hsv([0.5, 0.5, 0.5, 1.0]), [0.0, 0.0, 0.5, 1.0]);
+        assert_near(rgba_to_hsv([0.5, 0.25, 1.0, 1.0]), [260.0, 0.75, 1.0, 1.0]);
+        assert_near(hsv_to_rgba(240.0, 1.0, 0.5, 1.0), [0.0, 0.0, 0.5, 1.0]);
+        assert_near(hsv_to_rgba(-120.0, 1.0, 1.0, 1.0), [0.0, 0.0, 1.0, 1.0]);
+        let c = [0.2, 0.7, 0.4, 0.3];
+        let hsv = rgba_to_hsv(c);
+        assert_near(hsv_to_rgba(hsv[0], hsv[1], hsv[2], hsv[3]), c);
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_near(rgba_to_hsl([1.0, 0.0, 0.0, 1.0]), [0.0, 1.0, 0.5, 1.0]);
+        assert_near(hsl_to_rgba(0.0, 1.0, 0.5, 1.0), [1.0, 0.0, 0.0, 1.0]);
+        assert_near(hsl_to_rgba(360.0, 1.0, 0.5, 1.0), [1.0, 0.0, 0.0, 1.0]);
+        assert_near(hsl_to_rgba(-240.0, 1.0, 0.5, 1.0), [0.0, 1.0, 0.0, 1.0]);
+        assert_near(hsl_to_rgba(240.0, 1.0, 0.25, 0.5), [0.0, 0.0, 0.5, 0.5]);
+        assert_near(rgba_to_hsl([0.0, 0.0, 0.5, 0.5]), [240.0, 1.0, 0.25, 0.5]);
+        assert_near(rgba_to_hsl([0.5, 0.5, 0.5, 1.0]), [0.0, 0.0, 0.5, 1.0]);
+        assert_near(hsl_to_rgba(120.0, 0.0, 0.5, 1.0), [0.5, 0.5, 0.5, 1.0]);
+        let c = [0.2, 0.6, 0.9, 1.0];
+        let hsl = rgba_to_hsl(c);
+        assert_near(hsl_to_rgba(hsl[0], hsl[1], hsl[2], hsl[3]), c);
+    }
+
+    #[test]
+    fn test_blend() {
+        let dst = [0.2, 0.6, 1.0, 1.0];
+        let src = [0.5, 0.5, 0.25, 1.0];
+        assert_near(blend(dst, src, BlendMode::Normal), src);
+        assert_near(blend(dst, src, BlendMode::Multiply), [0.1, 0.3, 0.25, 1.0]);
+        assert_near(blend(dst, src, BlendMode::Screen), [0.6, 0.8, 1.0, 1.0]);
+        assert_near(blend(dst, src, BlendMode::Overlay), [0.2, 0.6, 1.0, 1.0]);
+        assert_near(blend(dst, src, BlendMode::Add), [0.7, 1.0, 1.0, 1.0]);
+        assert_near(blend(dst, src, BlendMode::Subtract), [0.0, 0.1, 0.75, 1.0]);
+
+        let half = [1.0, 0.0, 0.0, 0.5];
+        let blue = [0.0, 0.0, 1.0, 1.0];
+        assert_near(blend(blue, half, BlendMode::Normal), [0.5, 0.0, 0.5, 1.0]);
+        assert_near(blend(blue, half, BlendMode::Multiply), [0.0, 0.0, 0.5, 1.0]);
+        assert_near(blend(TRANSPARENT, half, BlendMode::Multiply), half);
+        assert_eq!(blend(TRANSPARENT, TRANSPARENT, BlendMode::Add), TRANSPARENT);
+    }
+
+    #[test]
+    fn test_named() {
+        assert_eq!(named::WHITE, [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(named::TRANSPARENT[3], 0.0);
+        let (gray, silver) = (hex("808080"), hex("c0c0c0"));
+        for i in 0..4 {
+            assert!((named::GRAY[i] - gray[i]).abs() < 0.000001);
+            assert!((named::SILVER[i] - silver[i]).abs() < 0.000001);
+        }
+    }
+
+    #[test]
+    fn test_into_color() {
+        assert_eq!([0.1, 0.2, 0.3, 0.4].into_color(), [0.1, 0.2, 0.3, 0.4]);
+        assert_eq!([0.1, 0.2, 0.3].into_color(), [0.1, 0.2, 0.3, 1.0]);
+        assert_eq!([255u8, 0, 51, 255].into_color(), [1.0, 0.0, 0.2, 1.0]);
+        assert_eq!(0.5.into_color(), [0.5, 0.5, 0.5, 1.0]);
+    }
+}
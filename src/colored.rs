@@ -2,32 +2,80 @@ use types::{Color, ColorComponent};
 use {Ellipse, Line, Rectangle};
 use radians::Radians;
 use math::hsv;
+use color::{blend, gamma_linear_to_srgb, gamma_srgb_to_linear, hex as color_hex, hsl_to_rgba,
+            hsv_to_rgba, rgba_to_hsl, rgba_to_hsv, AlphaMode, BlendMode, IntoColor};
 
 /// Implemented by contexts that contains color.
 pub trait Colored: Sized {
+    /// Returns the current color.
+    fn get_color(&self) -> Color;
+
+    /// Replaces the current color in place.
+    fn set_color(&mut self, color: Color);
+
+    /// Sets the color from any value that can be converted to a color.
+    ///
+    /// This is the by-value counterpart of `set_color`.
+    /// See `color::IntoColor` for the supported values.
+    #[inline(always)]
+    fn with_color<C: IntoColor>(mut self, color: C) -> Self {
+        self.set_color(color.into_color());
+        self
+    }
+
     /// Multiplies with red, green, blue and alpha values.
+    #[inline(always)]
     fn mul_rgba(self,
                 r: ColorComponent,
                 g: ColorComponent,
                 b: ColorComponent,
                 a: ColorComponent)
-                -> Self;
+                -> Self {
+        let c = self.get_color();
+        self.with_color([c[0] * r, c[1] * g, c[2] * b, c[3] * a])
+    }
+
+    /// Multiplies with red, green, blue and alpha values,
+    /// clamping the result to [0, 1].
+    ///
+    /// Use this when a factor might be negative or push a component above one.
+    #[inline(always)]
+    fn mul_rgba_clamped(self,
+                        r: ColorComponent,
+                        g: ColorComponent,
+                        b: ColorComponent,
+                        a: ColorComponent)
+                        -> Self {
+        self.mul_rgba(r, g, b, a).clamp_color()
+    }
+
+    /// Multiplies with red, green and blue values.
+    ///
+    /// Leaves alpha unchanged.
+    #[inline(always)]
+    fn mul_rgb(self, r: ColorComponent, g: ColorComponent, b: ColorComponent) -> Self {
+        self.mul_rgba(r, g, b, 1.0)
+    }
 
     /// Mixes the current color with white.
     ///
     /// 0 is black and 1 is white.
+    /// The result is not clamped, so follow with `clamp_color`
+    /// when `f` is outside [0, 1].
     #[inline(always)]
     fn tint(self, f: ColorComponent) -> Self {
-        self.mul_rgba(f, f, f, 1.0)
+        self.mul_rgb(f, f, f)
     }
 
     /// Mixes the current color with black.
     ///
     /// 0 is white and 1 is black.
+    /// The result is not clamped, so follow with `clamp_color`
+    /// when `f` is outside [0, 1].
     #[inline(always)]
     fn shade(self, f: ColorComponent) -> Self {
         let f = 1.0 - f;
-        self.mul_rgba(f, f, f, 1.0)
+        self.mul_rgb(f, f, f)
     }
 
     /// Rotates hue by degrees.
@@ -37,80 +85,825 @@ pub trait Colored: Sized {
         self.hue_rad(angle * pi / 180.0)
     }
 
+    /// Rotates hue by turns.
+    ///
+    /// One turn is a full rotation around the color wheel.
+    #[inline(always)]
+    fn hue_turns(self, turns: ColorComponent) -> Self {
+        let two_pi: ColorComponent = Radians::_360();
+        self.hue_rad(turns * two_pi)
+    }
+
+    /// Rotates hue by `t * speed` turns, for animating hue over time.
+    ///
+    /// Whole turns are removed before rotating,
+    /// so large values of `t` do not lose precision.
+    #[inline(always)]
+    fn hue_cycle(self, t: ColorComponent, speed: ColorComponent) -> Self {
+        self.hue_turns((t * speed).fract())
+    }
+
     /// Rotates hue by radians.
-    fn hue_rad(self, angle: ColorComponent) -> Self;
+    ///
+    /// The hue is rotated in the YIQ color space, see `math::hsv`, which keeps the luminance.
+    /// This is not the hexcone hue used by `set_hue` and `color::rgba_to_hsv`,
+    /// so rotating by an angle gives a different color
+    /// than `set_hue` with the current hexcone hue plus the same angle.
+    #[inline(always)]
+    fn hue_rad(self, angle: ColorComponent) -> Self {
+        self.hsv_adjust(angle, 1.0, 1.0)
+    }
+
+    /// Sets hue in radians, keeping saturation and value.
+    ///
+    /// 0 is red. Grey colors have no hue and stay grey.
+    /// This uses the hexcone HSV model of `color::rgba_to_hsv`,
+    /// not the YIQ rotation of `hue_rad`, so the two do not agree for the same angle.
+    #[inline(always)]
+    fn set_hue(self, hue_rad: ColorComponent) -> Self {
+        let pi: ColorComponent = Radians::_180();
+        let hsv = rgba_to_hsv(self.get_color());
+        self.with_color(hsv_to_rgba(hue_rad * 180.0 / pi, hsv[1], hsv[2], hsv[3]))
+    }
+
+    /// Multiplies saturation.
+    ///
+    /// 0 is grey and 1 leaves the color unchanged.
+    #[inline(always)]
+    fn saturate(self, f: ColorComponent) -> Self {
+        self.hsv_adjust(0.0, f, 1.0)
+    }
+
+    /// Multiplies value (brightness).
+    #[inline(always)]
+    fn value(self, f: ColorComponent) -> Self {
+        self.hsv_adjust(0.0, 1.0, f)
+    }
+
+    /// Rotates hue by radians and multiplies saturation and value.
+    #[inline(always)]
+    fn hsv_adjust(self,
+                  h_rad: ColorComponent,
+                  s: ColorComponent,
+                  v: ColorComponent)
+                  -> Self {
+        let c = self.get_color();
+        self.with_color(hsv(c, h_rad, s, v))
+    }
+
+    /// Sets alpha, leaving red, green and blue unchanged.
+    #[inline(always)]
+    fn with_alpha(self, a: ColorComponent) -> Self {
+        let c = self.get_color();
+        self.with_color([c[0], c[1], c[2], a])
+    }
+
+    /// Multiplies with alpha value.
+    #[inline(always)]
+    fn mul_alpha(self, a: ColorComponent) -> Self {
+        self.mul_rgba(1.0, 1.0, 1.0, a)
+    }
+
+    /// Inverts red, green and blue, leaving alpha unchanged.
+    #[inline(always)]
+    fn invert(self) -> Self {
+        let c = self.get_color();
+        self.with_color([1.0 - c[0], 1.0 - c[1], 1.0 - c[2], c[3]])
+    }
+
+    /// Inverts red, green, blue and alpha.
+    #[inline(always)]
+    fn invert_rgba(self) -> Self {
+        let c = self.get_color();
+        self.with_color([1.0 - c[0], 1.0 - c[1], 1.0 - c[2], 1.0 - c[3]])
+    }
+
+    /// Replaces red, green and blue with the luminance of the color.
+    ///
+    /// Uses the Rec. 709 weights. Leaves alpha unchanged.
+    #[inline(always)]
+    fn grayscale(self) -> Self {
+        self.grayscale_weighted([0.2126, 0.7152, 0.0722])
+    }
+
+    /// Replaces red, green and blue with a weighted sum of them.
+    ///
+    /// Leaves alpha unchanged.
+    #[inline(always)]
+    fn grayscale_weighted(self, weights: [ColorComponent; 3]) -> Self {
+        let c = self.get_color();
+        let l = weights[0] * c[0] + weights[1] * c[1] + weights[2] * c[2];
+        self.with_color([l, l, l, c[3]])
+    }
+
+    /// Converts from sRGB to linear color space.
+    ///
+    /// Leaves alpha unchanged.
+    /// Components below zero follow the linear segment of the curve
+    /// and components above one follow the power segment; no clamping is done.
+    #[inline(always)]
+    fn to_linear(self) -> Self {
+        let c = self.get_color();
+        self.with_color(gamma_srgb_to_linear(c))
+    }
+
+    /// Converts from linear to sRGB color space.
+    ///
+    /// Leaves alpha unchanged.
+    /// Components below zero follow the linear segment of the curve
+    /// and components above one follow the power segment; no clamping is done.
+    #[inline(always)]
+    fn to_srgb(self) -> Self {
+        let c = self.get_color();
+        self.with_color(gamma_linear_to_srgb(c))
+    }
+
+    /// Linearly interpolates every component from the current color to a target color.
+    ///
+    /// 0 is the current color and 1 is the target color.
+    /// Factors outside [0, 1] extrapolate.
+    #[inline(always)]
+    fn lerp_color(self, target: Color, t: ColorComponent) -> Self {
+        let c = self.get_color();
+        let u = 1.0 - t;
+        self.with_color([c[0] * u + target[0] * t,
+                         c[1] * u + target[1] * t,
+                         c[2] * u + target[2] * t,
+                         c[3] * u + target[3] * t])
+    }
+
+    /// Raises red, green and blue to a power.
+    ///
+    /// Negative components are clamped to zero first.
+    /// Leaves alpha unchanged.
+    #[inline(always)]
+    fn gamma(self, exponent: ColorComponent) -> Self {
+        #[inline(always)]
+        fn pow(f: ColorComponent, exponent: ColorComponent) -> ColorComponent {
+            if f <= 0.0 { 0.0 } else { f.powf(exponent) }
+        }
+
+        let c = self.get_color();
+        self.with_color([pow(c[0], exponent), pow(c[1], exponent), pow(c[2], exponent), c[3]])
+    }
+
+    /// Clamps all components to [0, 1].
+    #[inline(always)]
+    fn clamp_color(self) -> Self {
+        self.clamp_color_range(0.0, 1.0)
+    }
+
+    /// Clamps all components to [min, max].
+    #[inline(always)]
+    fn clamp_color_range(self, min: ColorComponent, max: ColorComponent) -> Self {
+        let c = self.get_color();
+        self.with_color([c[0].max(min).min(max),
+                         c[1].max(min).min(max),
+                         c[2].max(min).min(max),
+                         c[3].max(min).min(max)])
+    }
+
+    /// Sets the color from hexadecimal color format.
+    ///
+    /// Panics if the string is not a valid hexadecimal color.
+    /// See `color::color_from_hex` for the supported formats.
+    #[inline(always)]
+    fn hex(self, hex: &str) -> Self {
+        self.with_color(color_hex(hex))
+    }
+
+    /// Multiplies lightness in the HSL color space.
+    ///
+    /// The lightness is clamped to [0, 1].
+    /// Leaves alpha unchanged.
+    #[inline(always)]
+    fn lightness(self, f: ColorComponent) -> Self {
+        let hsl = rgba_to_hsl(self.get_color());
+        let l = (hsl[2] * f).clamp(0.0, 1.0);
+        self.with_color(hsl_to_rgba(hsl[0], hsl[1], l, hsl[3]))
+    }
+
+    /// Multiplies red, green and blue with alpha.
+    #[inline(always)]
+    fn premultiply(self) -> Self {
+        let a = self.get_color()[3];
+        self.mul_rgb(a, a, a)
+    }
+
+    /// Divides red, green and blue by alpha.
+    ///
+    /// Leaves the color unchanged when alpha is zero,
+    /// since the original color can not be recovered.
+    #[inline(always)]
+    fn unpremultiply(self) -> Self {
+        let a = self.get_color()[3];
+        if a == 0.0 {
+            self
+        } else {
+            self.mul_rgb(1.0 / a, 1.0 / a, 1.0 / a)
+        }
+    }
+
+    /// Converts between straight and premultiplied alpha.
+    ///
+    /// Leaves the color unchanged if the modes are the same.
+    #[inline(always)]
+    fn convert_alpha(self, from: AlphaMode, to: AlphaMode) -> Self {
+        match (from, to) {
+            (AlphaMode::Straight, AlphaMode::Premultiplied) => self.premultiply(),
+            (AlphaMode::Premultiplied, AlphaMode::Straight) => self.unpremultiply(),
+            _ => self,
+        }
+    }
+
+    /// Multiplies with red, green, blue and alpha values,
+    /// for a color stored with an alpha mode.
+    ///
+    /// With premultiplied alpha, red, green and blue are also multiplied by the alpha factor,
+    /// so the result is the same as `mul_rgba` on the straight color.
+    /// Methods that leave alpha unchanged, such as `tint` and `shade`,
+    /// work the same in both modes.
+    #[inline(always)]
+    fn mul_rgba_in(self,
+                   r: ColorComponent,
+                   g: ColorComponent,
+                   b: ColorComponent,
+                   a: ColorComponent,
+                   mode: AlphaMode)
+                   -> Self {
+        match mode {
+            AlphaMode::Straight => self.mul_rgba(r, g, b, a),
+            AlphaMode::Premultiplied => self.mul_rgba(r * a, g * a, b * a, a),
+        }
+    }
+
+    /// Composites a source color over the current color,
+    /// where both are stored with an alpha mode.
+    ///
+    /// With straight alpha this is the same as `over`.
+    /// With premultiplied alpha, each component is `src + dst * (1 - src_alpha)`.
+    #[inline(always)]
+    fn over_in(self, src: Color, mode: AlphaMode) -> Self {
+        match mode {
+            AlphaMode::Straight => self.over(src),
+            AlphaMode::Premultiplied => {
+                let c = self.get_color();
+                let u = 1.0 - src[3];
+                self.with_color([src[0] + c[0] * u,
+                                 src[1] + c[1] * u,
+                                 src[2] + c[2] * u,
+                                 src[3] + c[3] * u])
+            }
+        }
+    }
+
+    /// Blends a source color over the current color with a blend mode.
+    ///
+    /// See `color::blend` for how alpha is handled.
+    #[inline(always)]
+    fn blend(self, src: Color, mode: BlendMode) -> Self {
+        let c = self.get_color();
+        self.with_color(blend(c, src, mode))
+    }
+
+    /// Returns the luminance of the color.
+    ///
+    /// Uses the Rec. 709 weights on red, green and blue, ignoring alpha.
+    #[inline(always)]
+    fn luminance(&self) -> ColorComponent {
+        let c = self.get_color();
+        0.2126 * c[0] + 0.7152 * c[1] + 0.0722 * c[2]
+    }
+
+    /// Returns true if the luminance is below a threshold.
+    #[inline(always)]
+    fn is_dark(&self, threshold: ColorComponent) -> bool {
+        self.luminance() < threshold
+    }
+
+    /// Rounds red, green and blue to a number of bits per channel.
+    ///
+    /// Each channel gets `2^bits` evenly spaced levels from 0 to 1.
+    /// `bits` is clamped to [1, 16]. Leaves alpha unchanged.
+    #[inline(always)]
+    fn quantize(self, bits: u8) -> Self {
+        let levels = ((1u32 << bits.clamp(1, 16)) - 1) as ColorComponent;
+        let c = self.get_color();
+        let q = |f: ColorComponent| (f * levels).round() / levels;
+        self.with_color([q(c[0]), q(c[1]), q(c[2]), c[3]])
+    }
+
+    /// Rounds red, green and blue to a number of bits per channel,
+    /// with ordered dithering by pixel position.
+    ///
+    /// Uses a 4x4 Bayer matrix, so neighbouring pixels round differently
+    /// and the average over an area stays close to the original color.
+    /// See `quantize` for how `bits` is used.
+    #[inline(always)]
+    fn quantize_dither(self, bits: u8, x: u32, y: u32) -> Self {
+        const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+        let levels = ((1u32 << bits.clamp(1, 16)) - 1) as ColorComponent;
+        let threshold = (BAYER[(y % 4) as usize][(x % 4) as usize] as ColorComponent + 0.5) / 16.0;
+        let c = self.get_color();
+        let q = |f: ColorComponent| (f * levels + threshold).floor() / levels;
+        self.with_color([q(c[0]), q(c[1]), q(c[2]), c[3]])
+    }
+
+    /// Scales red, green and blue around the midpoint 0.5.
+    ///
+    /// 1 leaves the color unchanged, above 1 increases contrast
+    /// and below 1 moves the color towards grey, where 0 gives grey.
+    /// The result is not clamped, so follow with `clamp_color`
+    /// when `amount` is above 1. Leaves alpha unchanged.
+    #[inline(always)]
+    fn contrast(self, amount: ColorComponent) -> Self {
+        let c = self.get_color();
+        let f = |x: ColorComponent| 0.5 + (x - 0.5) * amount;
+        self.with_color([f(c[0]), f(c[1]), f(c[2]), c[3]])
+    }
+
+    /// Adds an amount to red, green and blue, clamping to [0, 1].
+    ///
+    /// Unlike `tint`, this brightens dark and light colors by the same amount.
+    /// Leaves alpha unchanged.
+    #[inline(always)]
+    fn brighten(self, amount: ColorComponent) -> Self {
+        let c = self.get_color();
+        let f = |x: ColorComponent| (x + amount).clamp(0.0, 1.0);
+        self.with_color([f(c[0]), f(c[1]), f(c[2]), c[3]])
+    }
+
+    /// Subtracts an amount from red, green and blue, clamping to [0, 1].
+    ///
+    /// See `brighten`.
+    #[inline(always)]
+    fn darken(self, amount: ColorComponent) -> Self {
+        self.brighten(-amount)
+    }
+
+    /// Applies a sepia tone to red, green and blue.
+    ///
+    /// Uses the common sepia color matrix and clamps the result to [0, 1].
+    /// Leaves alpha unchanged.
+    #[inline(always)]
+    fn sepia(self) -> Self {
+        let c = self.get_color();
+        let f = |w: [ColorComponent; 3]| {
+            (w[0] * c[0] + w[1] * c[1] + w[2] * c[2]).clamp(0.0, 1.0)
+        };
+        self.with_color([f([0.393, 0.769, 0.189]),
+                         f([0.349, 0.686, 0.168]),
+                         f([0.272, 0.534, 0.131]),
+                         c[3]])
+    }
+
+    /// Composites a source color over the current color.
+    ///
+    /// This is the Porter-Duff source over operator with straight alpha,
+    /// the same as `blend` with `BlendMode::Normal`.
+    /// A transparent source leaves the current color unchanged,
+    /// and over a transparent destination the result is the source color.
+    #[inline(always)]
+    fn over(self, src: Color) -> Self {
+        self.blend(src, BlendMode::Normal)
+    }
+
+    /// Sets alpha to 1 if it is at least a threshold, and to 0 otherwise.
+    ///
+    /// This gives hard edges for cutout sprites. Leaves red, green and blue unchanged.
+    #[inline(always)]
+    fn alpha_test(self, threshold: ColorComponent) -> Self {
+        let a = if self.get_color()[3] >= threshold { 1.0 } else { 0.0 };
+        self.with_alpha(a)
+    }
+
+    /// Returns the Euclidean distance to another color.
+    ///
+    /// Red, green and blue are always included, and alpha if `include_alpha` is true.
+    #[inline(always)]
+    fn color_distance(&self, other: Color, include_alpha: bool) -> ColorComponent {
+        let c = self.get_color();
+        let n = if include_alpha { 4 } else { 3 };
+        c[..n].iter()
+            .zip(other[..n].iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum::<ColorComponent>()
+            .sqrt()
+    }
+
+    /// Returns the index of the closest color in a palette.
+    ///
+    /// Colors are compared by red, green and blue, see `color_distance`.
+    /// The first one is picked when several are equally close.
+    /// Returns `None` if the palette is empty.
+    #[inline(always)]
+    fn nearest(&self, palette: &[Color]) -> Option<usize> {
+        let mut best: Option<(usize, ColorComponent)> = None;
+        for (i, &p) in palette.iter().enumerate() {
+            let d = self.color_distance(p, false);
+            match best {
+                Some((_, best_d)) if best_d <= d => {}
+                _ => best = Some((i, d)),
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+
+    /// Applies a function to every component.
+    ///
+    /// The function gets the component and its index,
+    /// where 0 is red, 1 is green, 2 is blue and 3 is alpha.
+    #[inline(always)]
+    fn map_color<F: Fn(ColorComponent, usize) -> ColorComponent>(self, f: F) -> Self {
+        let c = self.get_color();
+        self.with_color([f(c[0], 0), f(c[1], 1), f(c[2], 2), f(c[3], 3)])
+    }
 }
 
 impl Colored for Color {
     #[inline(always)]
-    fn mul_rgba(self,
-                r: ColorComponent,
-                g: ColorComponent,
-                b: ColorComponent,
-                a: ColorComponent)
-                -> Self {
-        [self[0] * r, self[1] * g, self[2] * b, self[3] * a]
+    fn get_color(&self) -> Color {
+        *self
     }
 
     #[inline(always)]
-    fn hue_rad(self, angle: ColorComponent) -> Self {
-        hsv(self, angle, 1.0, 1.0)
+    fn set_color(&mut self, color: Color) {
+        *self = color;
     }
 }
 
 impl Colored for Line {
     #[inline(always)]
-    fn mul_rgba(mut self,
-                r: ColorComponent,
-                g: ColorComponent,
-                b: ColorComponent,
-                a: ColorComponent)
-                -> Self {
-        self.color = self.color.mul_rgba(r, g, b, a);
-        self
+    fn get_color(&self) -> Color {
+        self.color
     }
 
     #[inline(always)]
-    fn hue_rad(mut self, angle: ColorComponent) -> Self {
-        self.color = self.color.hue_rad(angle);
-        self
+    fn set_color(&mut self, color: Color) {
+        self.color = color;
     }
 }
 
 impl Colored for Ellipse {
     #[inline(always)]
-    fn mul_rgba(mut self,
-                r: ColorComponent,
-                g: ColorComponent,
-                b: ColorComponent,
-                a: ColorComponent)
-                -> Self {
-        self.color = self.color.mul_rgba(r, g, b, a);
-        self
+    fn get_color(&self) -> Color {
+        self.color
     }
 
     #[inline(always)]
-    fn hue_rad(mut self, angle: ColorComponent) -> Self {
-        self.color = self.color.hue_rad(angle);
-        self
+    fn set_color(&mut self, color: Color) {
+        self.color = color;
     }
 }
 
 impl Colored for Rectangle {
     #[inline(always)]
-    fn mul_rgba(mut self,
-                r: ColorComponent,
-                g: ColorComponent,
-                b: ColorComponent,
-                a: ColorComponent)
-                -> Self {
-        self.color = self.color.mul_rgba(r, g, b, a);
-        self
+    fn get_color(&self) -> Color {
+        self.color
     }
 
     #[inline(always)]
-    fn hue_rad(mut self, angle: ColorComponent) -> Self {
-        self.color = self.color.hue_rad(angle);
-        self
+    fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mul_rgb() {
+        let c: Color = [0.5, 0.5, 0.5, 0.5];
+        assert_eq!(c.mul_rgb(0.5, 1.0, 2.0), [0.25, 0.5, 1.0, 0.5]);
+        assert_eq!(c.mul_rgba(0.5, 1.0, 2.0, 0.5), [0.25, 0.5, 1.0, 0.25]);
+    }
+
+    #[test]
+    fn test_alpha() {
+        let c: Color = [0.1, 0.2, 0.3, 0.8];
+        let d = c.with_alpha(0.25);
+        assert_eq!(d, [0.1, 0.2, 0.3, 0.25]);
+        let e = c.mul_alpha(0.5);
+        assert_eq!(e, [0.1, 0.2, 0.3, 0.4]);
+    }
+
+    #[test]
+    fn test_saturate() {
+        let red: Color = [1.0, 0.0, 0.0, 1.0];
+        let a = red.saturate(1.0);
+        let b = red.saturate(0.0);
+        for i in 0..4 {
+            assert!((a[i] - red[i]).abs() < 0.01);
+        }
+        for &x in &b[0..3] {
+            assert!((x - 0.299).abs() < 0.00001);
+        }
+        assert_eq!(b[3], 1.0);
+
+        let c = red.value(0.5);
+        assert!((c[0] - 0.5).abs() < 0.01);
+        assert!(c[1].abs() < 0.01);
+    }
+
+    #[test]
+    fn test_invert() {
+        let c: Color = [0.1, 0.2, 0.7, 0.4];
+        assert_eq!(c.invert()[3], 0.4);
+        assert!((c.invert_rgba()[3] - 0.6).abs() < 0.00001);
+        let d = c.invert().invert();
+        let e = c.invert_rgba().invert_rgba();
+        for i in 0..4 {
+            assert!((d[i] - c[i]).abs() < 0.00001);
+            assert!((e[i] - c[i]).abs() < 0.00001);
+        }
+    }
+
+    #[test]
+    fn test_grayscale() {
+        let red: Color = [1.0, 0.0, 0.0, 0.5];
+        assert_eq!(red.grayscale(), [0.2126, 0.2126, 0.2126, 0.5]);
+        let green: Color = [0.0, 1.0, 0.0, 1.0];
+        assert_eq!(green.grayscale_weighted([0.3, 0.6, 0.1]), [0.6, 0.6, 0.6, 1.0]);
+    }
+
+    #[test]
+    fn test_linear_srgb() {
+        let colors: [Color; 3] = [[0.04045, 0.5, 1.0, 0.3],
+                                  [0.0, 0.2, 0.8, 1.0],
+                                  [-0.1, 0.01, 1.2, 0.0]];
+        for &c in &colors {
+            let d = c.to_linear().to_srgb();
+            for i in 0..4 {
+                assert!((d[i] - c[i]).abs() < 0.0001);
+            }
+            assert_eq!(c.to_linear()[3], c[3]);
+        }
+    }
+
+    #[test]
+    fn test_lerp_color() {
+        let a: Color = [0.0, 0.2, 1.0, 1.0];
+        let b: Color = [1.0, 0.4, 0.0, 0.0];
+        assert_eq!(a.lerp_color(b, 0.0), a);
+        assert_eq!(a.lerp_color(b, 1.0), b);
+        let c = a.lerp_color(b, 0.5);
+        let d = [0.5, 0.3, 0.5, 0.5];
+        for i in 0..4 {
+            assert!((c[i] - d[i]).abs() < 0.00001);
+        }
+    }
+
+    #[test]
+    fn test_gamma() {
+        let c: Color = [0.0, 0.5, 1.0, 0.5];
+        assert_eq!(c.gamma(1.0), c);
+        let d = c.gamma(2.2);
+        assert!(d[1] < 0.5);
+        assert!((d[1] - 0.5f32.powf(2.2)).abs() < 0.00001);
+        assert_eq!(d[2], 1.0);
+        assert_eq!(d[3], 0.5);
+        let e: Color = [-0.5, 0.0, 0.0, 1.0];
+        assert_eq!(e.gamma(0.5), [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_clamp_color() {
+        let c: Color = [-0.5, 0.5, 1.5, 2.0];
+        assert_eq!(c.clamp_color(), [0.0, 0.5, 1.0, 1.0]);
+        assert_eq!(c.clamp_color_range(0.0, 1.8), [0.0, 0.5, 1.5, 1.8]);
+        let d: Color = [0.1, 0.2, 0.3, 0.4];
+        assert_eq!(d.clamp_color(), d);
+    }
+
+    #[test]
+    fn test_hex() {
+        let c: Color = [0.0; 4];
+        assert_eq!(c.hex("#00f"), [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_lightness() {
+        let red: Color = [1.0, 0.0, 0.0, 0.5];
+        let a = red.lightness(0.5);
+        assert!((a[0] - 0.5).abs() < 0.00001 && a[1].abs() < 0.00001 && a[2].abs() < 0.00001);
+        assert_eq!(a[3], 0.5);
+        let b = red.lightness(4.0);
+        assert!((b[1] - 1.0).abs() < 0.00001);
+    }
+
+    #[test]
+    fn test_premultiply() {
+        let c: Color = [0.2, 0.4, 0.8, 0.5];
+        assert_eq!(c.premultiply(), [0.1, 0.2, 0.4, 0.5]);
+        let d = c.premultiply().unpremultiply();
+        for i in 0..4 {
+            assert!((d[i] - c[i]).abs() < 0.00001);
+        }
+        let e: Color = [0.2, 0.4, 0.8, 0.0];
+        assert_eq!(e.unpremultiply(), e);
+    }
+
+    #[test]
+    fn test_blend() {
+        let c: Color = [0.2, 0.6, 1.0, 1.0];
+        assert_eq!(c.blend([0.5, 0.5, 0.5, 1.0], BlendMode::Normal), [0.5, 0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_hue_turns() {
+        let c: Color = [0.8, 0.4, 0.1, 1.0];
+        let (a, b) = (c.hue_turns(0.5), c.hue_deg(180.0));
+        for i in 0..4 {
+            assert!((a[i] - b[i]).abs() < 0.00001);
+        }
+    }
+
+    #[test]
+    fn test_luminance() {
+        let black: Color = [0.0, 0.0, 0.0, 1.0];
+        let white: Color = [1.0, 1.0, 1.0, 0.5];
+        assert_eq!(black.luminance(), 0.0);
+        assert!((white.luminance() - 1.0).abs() < 0.00001);
+        assert!(black.is_dark(0.5));
+        assert!(!white.is_dark(0.5));
+        let red: Color = [1.0, 0.0, 0.0, 1.0];
+        assert_eq!(red.luminance(), red.grayscale()[0]);
+    }
+
+    #[test]
+    fn test_mul_rgba_clamped() {
+        let c: Color = [0.5, 0.8, 0.2, 1.0];
+        assert_eq!(c.mul_rgba_clamped(-1.0, 2.0, 0.5, -0.5), [0.0, 1.0, 0.1, 0.0]);
+        assert_eq!(c.tint(-2.0).clamp_color(), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(c.shade(-1.0).clamp_color(), [1.0, 1.0, 0.4, 1.0]);
+    }
+
+    #[test]
+    fn test_with_color() {
+        let c: Color = [0.0; 4];
+        assert_eq!(c.with_color([0.1, 0.2, 0.3]), [0.1, 0.2, 0.3, 1.0]);
+        assert_eq!(c.with_color([0u8, 255, 0, 255]), [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(c.with_color(0.25), [0.25, 0.25, 0.25, 1.0]);
+        let r = Rectangle::new([1.0; 4]).with_color([0.5, 0.5, 0.5, 0.5]);
+        assert_eq!(r.color, [0.5; 4]);
+    }
+
+    #[test]
+    fn test_quantize() {
+        let c: Color = [0.2, 0.49, 0.8, 0.3];
+        let q = c.quantize(8);
+        for i in 0..4 {
+            assert!((q[i] - c[i]).abs() <= 0.5 / 255.0);
+        }
+        assert_eq!(c.quantize(1), [0.0, 0.0, 1.0, 0.3]);
+        assert_eq!(c.quantize(0), c.quantize(1));
+        assert_eq!(c.quantize(2), [1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 0.3]);
+    }
+
+    #[test]
+    fn test_quantize_dither() {
+        let c: Color = [0.25, 0.0, 1.0, 1.0];
+        let mut sum = 0.0;
+        for y in 0..4 {
+            for x in 0..4 {
+                let q = c.quantize_dither(1, x, y);
+                assert!(q[0] == 0.0 || q[0] == 1.0);
+                assert_eq!((q[1], q[2], q[3]), (0.0, 1.0, 1.0));
+                sum += q[0];
+            }
+        }
+        assert_eq!(sum / 16.0, 0.25);
+    }
+
+    #[test]
+    fn test_hue_cycle() {
+        let c: Color = [0.8, 0.4, 0.1, 1.0];
+        let a = c.hue_cycle(1.0, 1.0);
+        let (b, d) = (c.hue_cycle(1000.25, 1.0), c.hue_turns(0.25));
+        for i in 0..4 {
+            assert!((a[i] - c[i]).abs() < 0.01);
+            assert!((b[i] - d[i]).abs() < 0.001);
+        }
+        assert_eq!(c.hue_cycle(0.5, 2.0), c.hue_turns(0.0));
+    }
+
+    #[test]
+    fn test_contrast() {
+        let c: Color = [0.25, 0.5, 1.0, 0.2];
+        assert_eq!(c.contrast(1.0), c);
+        assert_eq!(c.contrast(2.0), [0.0, 0.5, 1.5, 0.2]);
+        assert_eq!(c.contrast(2.0).clamp_color(), [0.0, 0.5, 1.0, 0.2]);
+        assert_eq!(c.contrast(0.0), [0.5, 0.5, 0.5, 0.2]);
+    }
+
+    #[test]
+    fn test_brighten_darken() {
+        let c: Color = [0.25, 0.5, 0.75, 0.2];
+        assert_eq!(c.brighten(0.0), c);
+        assert_eq!(c.brighten(0.25), [0.5, 0.75, 1.0, 0.2]);
+        assert_eq!(c.brighten(1.0), [1.0, 1.0, 1.0, 0.2]);
+        assert_eq!(c.darken(0.5), [0.0, 0.0, 0.25, 0.2]);
+        assert_eq!(c.darken(1.0), [0.0, 0.0, 0.0, 0.2]);
+    }
+
+    #[test]
+    fn test_sepia() {
+        let grey: Color = [0.5, 0.5, 0.5, 0.8];
+        let s = grey.sepia();
+        let expected = [0.6755, 0.6015, 0.4685, 0.8];
+        for i in 0..4 {
+            assert!((s[i] - expected[i]).abs() < 0.00001);
+        }
+        assert!(s[0] > s[1] && s[1] > s[2]);
+        assert_eq!([1.0, 1.0, 1.0, 1.0].sepia(), [1.0, 1.0, 0.937, 1.0]);
+    }
+
+    #[test]
+    fn test_over() {
+        let blue: Color = [0.0, 0.0, 1.0, 1.0];
+        assert_eq!(blue.over([1.0, 0.0, 0.0, 0.5]), [0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(blue.over([1.0, 0.0, 0.0, 0.0]), blue);
+        let clear: Color = [0.3, 0.3, 0.3, 0.0];
+        assert_eq!(clear.over([1.0, 0.0, 0.0, 0.5]), [1.0, 0.0, 0.0, 0.5]);
+        assert_eq!(clear.over([1.0, 0.0, 0.0, 0.0]), [0.0; 4]);
+        let a = [0.0, 0.0, 1.0, 0.5].over([1.0, 0.0, 0.0, 0.5]);
+        assert_eq!(a[3], 0.75);
+        assert!((a[0] - 2.0 / 3.0).abs() < 0.00001 && (a[2] - 1.0 / 3.0).abs() < 0.00001);
+    }
+
+    #[test]
+    fn test_set_hue() {
+        let c: Color = [0.2, 0.8, 0.5, 0.5];
+        let r = c.set_hue(0.0);
+        assert!((r[0] - 0.8).abs() < 0.00001 && (r[1] - 0.2).abs() < 0.00001);
+        assert!((r[2] - 0.2).abs() < 0.00001 && r[3] == 0.5);
+        let pi: ColorComponent = Radians::_180();
+        let b = c.set_hue(4.0 / 3.0 * pi);
+        assert!((b[0] - 0.2).abs() < 0.00001 && (b[2] - 0.8).abs() < 0.00001);
+        let grey: Color = [0.5, 0.5, 0.5, 1.0];
+        assert_eq!(grey.set_hue(1.0), grey);
+    }
+
+    #[test]
+    fn test_alpha_mode() {
+        let blue: Color = [0.0, 0.0, 1.0, 1.0];
+        let red: Color = [1.0, 0.0, 0.0, 0.5];
+        let premul_red = red.premultiply();
+        assert_eq!(premul_red, [0.5, 0.0, 0.0, 0.5]);
+        assert_eq!(red.convert_alpha(AlphaMode::Straight, AlphaMode::Premultiplied), premul_red);
+        assert_eq!(premul_red.convert_alpha(AlphaMode::Premultiplied, AlphaMode::Straight), red);
+        assert_eq!(red.convert_alpha(AlphaMode::Straight, AlphaMode::Straight), red);
+
+        assert_eq!(blue.over_in(red, AlphaMode::Straight), [0.5, 0.0, 0.5, 1.0]);
+        assert_eq!(blue.over_in(premul_red, AlphaMode::Premultiplied), [0.5, 0.0, 0.5, 1.0]);
+        // Treating a straight color as premultiplied gives too much red.
+        assert_eq!(blue.over_in(red, AlphaMode::Premultiplied), [1.0, 0.0, 0.5, 1.0]);
+
+        let half_blue: Color = [0.0, 0.0, 1.0, 0.5];
+        let a = half_blue.over_in(red, AlphaMode::Straight);
+        let b = half_blue.premultiply()
+            .over_in(premul_red, AlphaMode::Premultiplied)
+            .unpremultiply();
+        for i in 0..4 {
+            assert!((a[i] - b[i]).abs() < 0.00001);
+        }
+
+        assert_eq!(premul_red.mul_rgba_in(1.0, 1.0, 1.0, 0.5, AlphaMode::Premultiplied),
+                   red.mul_alpha(0.5).premultiply());
+        assert_eq!(red.mul_rgba_in(1.0, 1.0, 1.0, 0.5, AlphaMode::Straight), red.mul_alpha(0.5));
+    }
+
+    #[test]
+    fn test_alpha_test() {
+        let c: Color = [0.2, 0.4, 0.6, 0.5];
+        assert_eq!(c.alpha_test(0.49), [0.2, 0.4, 0.6, 1.0]);
+        assert_eq!(c.alpha_test(0.5), [0.2, 0.4, 0.6, 1.0]);
+        assert_eq!(c.alpha_test(0.51), [0.2, 0.4, 0.6, 0.0]);
+    }
+
+    #[test]
+    fn test_color_distance() {
+        let c: Color = [0.9, 0.1, 0.0, 1.0];
+        assert_eq!(c.color_distance(c, true), 0.0);
+        assert_eq!([0.0; 4].color_distance([0.0, 0.3, 0.4, 1.0], false), 0.5);
+        assert_eq!([0.0; 4].color_distance([0.0, 0.0, 0.0, 1.0], true), 1.0);
+
+        let palette = [[0.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [1.0, 0.0, 0.0, 1.0]];
+        assert_eq!(c.nearest(&palette), Some(2));
+        assert_eq!([0.1, 0.1, 0.1, 0.0].nearest(&palette), Some(0));
+        assert_eq!([0.4, 0.6, 0.0, 1.0].nearest(&palette), Some(1));
+        assert_eq!([0.5, 0.5, 0.0, 1.0].nearest(&palette), Some(0));
+        assert_eq!(c.nearest(&[]), None);
+    }
+
+    #[test]
+    fn test_map_color() {
+        let c: Color = [0.8, 0.4, 0.1, 0.5];
+        let l = c.luminance();
+        assert_eq!(c.map_color(|x, i| if i < 3 { l } else { x }), c.grayscale());
+        assert_eq!(c.map_color(|x, i| if i == 3 { x } else { 1.0 - x }), c.invert());
+        assert_eq!(c.map_color(|x, _| x), c);
     }
 }
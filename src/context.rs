@@ -1,7 +1,9 @@
 //! Transformation context
 
 use DrawState;
-use math::{abs_transform, identity, get_scale, Matrix2d, Scalar, Vec2d};
+use types::Rectangle;
+use math::{abs_transform, identity, invert, get_scale, multiply, transform_pos, Matrix2d, Scalar,
+           Vec2d};
 use Viewport;
 
 /// Drawing 2d context.
@@ -17,8 +19,18 @@ pub struct Context {
     pub draw_state: DrawState,
 }
 
+impl Default for Context {
+    #[inline(always)]
+    fn default() -> Context {
+        Context::new()
+    }
+}
+
 impl Context {
     /// Creates a new drawing context.
+    ///
+    /// The view and the current transform are identity,
+    /// the draw state is alpha blending and there is no viewport.
     #[inline(always)]
     pub fn new() -> Context {
         Context {
@@ -100,17 +112,242 @@ impl Context {
         self
     }
 
+    /// Computes the current transform relative to the view.
+    ///
+    /// The current transform already includes the view,
+    /// and is the matrix used by back-ends to draw.
+    /// It is `multiply(view, model)`, where the model is applied first
+    /// to go from local to view coordinates, then the view to go to the screen.
+    ///
+    /// Returns `None` if the view is not invertible.
+    #[inline(always)]
+    pub fn model(&self) -> Option<Matrix2d> {
+        invert(self.view).map(|m| multiply(m, self.transform))
+    }
+
+    /// Translates the view in view coordinates.
+    ///
+    /// The current transform moves with the view,
+    /// so everything drawn with the context is panned.
+    #[inline(always)]
+    pub fn pan(self, dx: Scalar, dy: Scalar) -> Self {
+        use Transformed;
+
+        let view = self.view.trans(dx, dy);
+        self.replace_view(view)
+    }
+
+    /// Zooms the view around a point in world coordinates.
+    ///
+    /// The point stays at the same location on the screen.
+    /// The current transform moves with the view, like `pan`.
+    /// See `screen_to_world` for the coordinate systems.
+    #[inline(always)]
+    pub fn zoom_view_at(self, factor: Scalar, world_point: Vec2d) -> Self {
+        use Transformed;
+
+        let view = self.view.zoom_about(factor, world_point);
+        self.replace_view(view)
+    }
+
+    /// Zooms the view around a point in screen coordinates, such as the mouse position.
+    ///
+    /// This is `zoom_view_at` with the point converted by `screen_to_world`.
+    /// Leaves the context unchanged if the point can not be converted.
+    #[inline(always)]
+    pub fn zoom_at_screen(self, factor: Scalar, screen_point: Vec2d) -> Self {
+        match self.screen_to_world(screen_point) {
+            Some(world_point) => self.zoom_view_at(factor, world_point),
+            None => self,
+        }
+    }
+
+    /// Replaces the view, keeping the current transform relative to the view.
+    ///
+    /// The current transform is left unchanged if the old view is not invertible.
+    #[inline(always)]
+    fn replace_view(mut self, view: Matrix2d) -> Self {
+        if let Some(model) = self.model() {
+            self.transform = multiply(view, model);
+        }
+        self.view = view;
+        self
+    }
+
+    /// Converts from screen coordinates to world coordinates.
+    ///
+    /// Screen coordinates are in points (window coordinates),
+    /// with origin in the upper left corner of the viewport,
+    /// like mouse positions.
+    /// World coordinates are in the view coordinate system.
+    ///
+    /// Returns `None` if the context has no viewport, for example after `new_abs`,
+    /// or if the view is not invertible.
+    /// See `ndc_to_world` for converting from normalized device coordinates.
+    #[inline(always)]
+    pub fn screen_to_world(&self, p: Vec2d) -> Option<Vec2d> {
+        self.viewport.and_then(|v| self.ndc_to_world(transform_pos(v.abs_transform(), p)))
+    }
+
+    /// Converts from world coordinates to screen coordinates.
+    ///
+    /// This is the reverse of `screen_to_world`.
+    /// Returns `None` if the context has no viewport or the viewport is empty.
+    #[inline(always)]
+    pub fn world_to_screen(&self, p: Vec2d) -> Option<Vec2d> {
+        self.viewport
+            .and_then(|v| invert(v.abs_transform()))
+            .map(|m| transform_pos(m, self.world_to_ndc(p)))
+    }
+
+    /// Converts from normalized device coordinates (NDC) to world coordinates.
+    ///
+    /// NDC is the default coordinate system, centered
+    /// with the x axis pointing to the right and the y axis pointing up,
+    /// and goes from -1 to 1 across the viewport.
+    /// World coordinates are in the view coordinate system.
+    ///
+    /// Returns `None` if the view is not invertible.
+    #[inline(always)]
+    pub fn ndc_to_world(&self, p: Vec2d) -> Option<Vec2d> {
+        invert(self.view).map(|m| transform_pos(m, p))
+    }
+
+    /// Converts from world coordinates to normalized device coordinates (NDC).
+    ///
+    /// See `ndc_to_world` for the coordinate systems.
+    #[inline(always)]
+    pub fn world_to_ndc(&self, p: Vec2d) -> Vec2d {
+        transform_pos(self.view, p)
+    }
+
+    /// Moves the current transform to look from a point towards a target point.
+    ///
+    /// The eye is moved to the origin and the x axis points towards the target.
+    /// Leaves the orientation unchanged if the eye and the target are the same point.
+    /// Use `store_view` to make this the new view.
+    #[inline(always)]
+    pub fn look_at(self, eye: Vec2d, target: Vec2d) -> Self {
+        use Transformed;
+
+        let (dx, dy) = (target[0] - eye[0], target[1] - eye[1]);
+        self.orient(dx, -dy).trans(-eye[0], -eye[1])
+    }
+
     /// Computes the current view size.
     #[inline(always)]
     pub fn get_view_size(&self) -> Vec2d {
         let scale = get_scale(self.view);
         [2.0 / scale[0], 2.0 / scale[1]]
     }
+
+    /// Computes the aspect ratio (width / height) of the current view.
+    ///
+    /// See `Rectangled::aspect_ratio` for how a zero height is handled.
+    #[inline(always)]
+    pub fn view_aspect_ratio(&self) -> Scalar {
+        use Rectangled;
+
+        let size = self.get_view_size();
+        [0.0, 0.0, size[0], size[1]].aspect_ratio()
+    }
+
+    /// Sets the view to absolute coordinates of a given size.
+    ///
+    /// The view has origin in the upper left corner, like `new_abs`.
+    /// The current transform keeps its place relative to the view, like `pan`.
+    #[inline(always)]
+    pub fn set_view_size(self, w: Scalar, h: Scalar) -> Self {
+        self.replace_view(abs_transform(w, h))
+    }
+
+    /// Sets the view to show a rectangle in world coordinates, centered and as large as possible.
+    ///
+    /// The rectangle is expanded by padding on all sides,
+    /// and the aspect ratio of the current view size is kept,
+    /// so the rectangle fills the view in one direction and is letterboxed in the other.
+    /// The new view has the y axis pointing down, like `set_view_size`.
+    /// The current transform keeps its place relative to the view, like `pan`.
+    /// Leaves the view unchanged if the padded rectangle has zero width and height.
+    #[inline(always)]
+    pub fn fit_view_to(self, world_rect: Rectangle, padding: Scalar) -> Self {
+        use {Rectangled, Transformed};
+
+        let r = world_rect.normalize().expand(padding);
+        if r[2] <= 0.0 && r[3] <= 0.0 {
+            return self;
+        }
+        let aspect = self.view_aspect_ratio();
+        let (w, h) = if r[3] == 0.0 || r[2] > r[3] * aspect {
+            (r[2], r[2] / aspect)
+        } else {
+            (r[3] * aspect, r[3])
+        };
+        let center = r.center();
+        self.replace_view(abs_transform(w, h).trans(0.5 * w - center[0], 0.5 * h - center[1]))
+    }
+
+    /// Computes the region of world coordinates that is visible.
+    ///
+    /// Returns the bounds of the viewport in world coordinates as `[x, y, w, h]`,
+    /// or `None` if the view is not invertible.
+    /// See `ndc_to_world` for the coordinate systems.
+    #[inline(always)]
+    pub fn view_rectangle(&self) -> Option<Rectangle> {
+        use Transformed;
+
+        invert(self.view).map(|m| m.transform_rectangle_bounds([-1.0, -1.0, 2.0, 2.0]))
+    }
+
+    /// Adjusts the view to show an area with a target aspect ratio (width / height).
+    ///
+    /// The current view is assumed to match the window, for example after `set_view_size`
+    /// with the window size, and the area has the target aspect and the current view height.
+    /// The center of the view stays fixed,
+    /// and the current transform keeps its place relative to the view, like `pan`.
+    /// See `AspectMode` for how the area is fitted.
+    /// Leaves the view unchanged if it is not invertible or the aspect ratio is not positive.
+    #[inline(always)]
+    pub fn constrain_aspect(self, target_aspect: Scalar, mode: AspectMode) -> Self {
+        use Transformed;
+
+        let center = match self.ndc_to_world([0.0, 0.0]) {
+            Some(center) => center,
+            None => return self,
+        };
+        let aspect = self.view_aspect_ratio();
+        if aspect <= 0.0 || target_aspect <= 0.0 {
+            return self;
+        }
+        let size = self.get_view_size();
+        let (w, h) = (size[0], size[1]);
+        let area_w = h * target_aspect;
+        let (new_w, new_h) = match mode {
+            AspectMode::Letterbox if aspect < target_aspect => (area_w, area_w / aspect),
+            AspectMode::Crop if aspect > target_aspect => (area_w, area_w / aspect),
+            AspectMode::Letterbox | AspectMode::Crop => (w, h),
+            AspectMode::Stretch => (area_w, h),
+        };
+        let view = self.view.scale_about(w / new_w, h / new_h, center);
+        self.replace_view(view)
+    }
+}
+
+/// How `Context::constrain_aspect` fits an area with a different aspect ratio.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AspectMode {
+    /// Shows the whole area, leaving extra space on two sides.
+    Letterbox,
+    /// Fills the view with the area, cutting off two sides.
+    Crop,
+    /// Shows exactly the area, scaling width and height differently.
+    Stretch,
 }
 
 #[cfg(test)]
 mod test {
     use super::Context;
+    use math::{transform_pos, Scalar};
 
     #[test]
     fn test_context() {
@@ -145,4 +382,239 @@ mod test {
         assert!((transform[0][0] - 2.0).abs() < 0.00001);
         assert!((transform[1][1] - 3.0).abs() < 0.00001);
     }
+
+    #[test]
+    fn test_look_at() {
+        use Transformed;
+
+        let c = Context::new().look_at([10.0, 10.0], [10.0, 20.0]).store_view();
+        let p = c.transform_point([10.0, 20.0]);
+        assert!((p[0] - 10.0).abs() < 0.00001 && p[1].abs() < 0.00001);
+        let q = c.transform_point([10.0, 10.0]);
+        assert!(q[0].abs() < 0.00001 && q[1].abs() < 0.00001);
+        assert_eq!(c.view, c.transform);
+
+        let d = Context::new().rot_deg(30.0);
+        let e = d.look_at([1.0, 2.0], [1.0, 2.0]);
+        assert_eq!(e.transform, d.trans(-1.0, -2.0).transform);
+    }
+
+    #[test]
+    fn test_set_view_size() {
+        use Transformed;
+
+        let c = Context::new().set_view_size(640.0, 480.0);
+        let size = c.get_view_size();
+        assert!((size[0] - 640.0).abs() < 0.001 && (size[1] - 480.0).abs() < 0.001);
+        assert_eq!(c.view, Context::new_abs(640.0, 480.0).view);
+        assert_eq!(c.transform, c.view);
+
+        let d = Context::new_abs(640.0, 480.0).trans(10.0, 20.0).set_view_size(320.0, 240.0);
+        let e = Context::new_abs(320.0, 240.0).trans(10.0, 20.0);
+        for (a, b) in d.transform.iter().zip(e.transform.iter()) {
+            for (a, b) in a.iter().zip(b.iter()) {
+                assert!((a - b).abs() < 0.00001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pan_zoom() {
+        use Transformed;
+
+        fn assert_pos(a: [Scalar; 2], b: [Scalar; 2]) {
+            assert!((a[0] - b[0]).abs() < 0.00001 && (a[1] - b[1]).abs() < 0.00001);
+        }
+
+        let c = Context::new_abs(640.0, 480.0).trans(100.0, 50.0);
+        let d = c.pan(10.0, 20.0);
+        assert_eq!(transform_pos(d.view, [0.0, 0.0]), transform_pos(c.view, [10.0, 20.0]));
+        assert_pos(transform_pos(d.transform, [0.0, 0.0]),
+                   transform_pos(c.transform, [10.0, 20.0]));
+        let t = d.get_translation();
+        assert!((t[0] - 100.0).abs() < 0.001 && (t[1] - 50.0).abs() < 0.001);
+
+        let p = [100.0, 50.0];
+        let e = c.zoom_view_at(2.0, p);
+        assert_pos(transform_pos(e.view, p), transform_pos(c.view, p));
+        assert_pos(transform_pos(e.transform, [0.0, 0.0]),
+                   transform_pos(c.transform, [0.0, 0.0]));
+        assert_pos(transform_pos(e.transform, [10.0, 0.0]),
+                   transform_pos(c.transform, [20.0, 0.0]));
+        let size = e.get_view_size();
+        assert!((size[0] - 320.0).abs() < 0.001 && (size[1] - 240.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_zoom_at_screen() {
+        use Viewport;
+
+        let viewport = Viewport {
+            rect: [0, 0, 640, 480],
+            draw_size: [640, 480],
+            window_size: [640, 480],
+        };
+        let c = Context::new_viewport(viewport).pan(10.0, 20.0);
+        let p = [100.0, 50.0];
+        let a = c.screen_to_world(p).unwrap();
+        let d = c.zoom_at_screen(2.0, p);
+        let b = d.screen_to_world(p).unwrap();
+        assert!((a[0] - b[0]).abs() < 0.001 && (a[1] - b[1]).abs() < 0.001);
+        assert_eq!(d.view, c.zoom_view_at(2.0, a).view);
+
+        let e = Context::new_abs(640.0, 480.0);
+        assert_eq!(e.zoom_at_screen(2.0, p).view, e.view);
+    }
+
+    #[test]
+    fn test_screen_to_world() {
+        use Viewport;
+
+        fn assert_pos(a: [Scalar; 2], b: [Scalar; 2]) {
+            assert!((a[0] - b[0]).abs() < 0.001 && (a[1] - b[1]).abs() < 0.001);
+        }
+
+        let viewport = Viewport {
+            rect: [0, 0, 1280, 960],
+            draw_size: [1280, 960],
+            window_size: [640, 480],
+        };
+        let c = Context::new_viewport(viewport);
+        assert_pos(c.screen_to_world([320.0, 240.0]).unwrap(), [320.0, 240.0]);
+        assert_pos(c.world_to_screen([640.0, 0.0]).unwrap(), [640.0, 0.0]);
+
+        let d = c.pan(10.0, 20.0).zoom_view_at(2.0, [0.0, 0.0]);
+        assert_pos(d.screen_to_world([320.0, 240.0]).unwrap(), [155.0, 110.0]);
+        let p = [100.0, 50.0];
+        assert_pos(d.screen_to_world(d.world_to_screen(p).unwrap()).unwrap(), p);
+
+        let e = Context::new_abs(640.0, 480.0);
+        assert_eq!(e.screen_to_world(p), None);
+        assert_eq!(e.world_to_screen(p), None);
+    }
+
+    #[test]
+    fn test_ndc_to_world() {
+        let c = Context::new_abs(640.0, 480.0).pan(10.0, 20.0).zoom_view_at(2.0, [0.0, 0.0]);
+        let p = [100.0, 50.0];
+        let q = c.ndc_to_world(c.world_to_ndc(p)).unwrap();
+        assert!((q[0] - p[0]).abs() < 0.001 && (q[1] - p[1]).abs() < 0.001);
+        assert_eq!(Context::new_abs(640.0, 480.0).world_to_ndc([320.0, 240.0]), [0.0, 0.0]);
+
+        let mut d = Context::new();
+        d.view = [[0.0; 3]; 2];
+        assert_eq!(d.ndc_to_world(p), None);
+    }
+
+    #[test]
+    fn test_default() {
+        use math::identity;
+        use DrawState;
+
+        let c = Context::default();
+        assert_eq!(c.view, identity());
+        assert_eq!(c.transform, identity());
+        assert_eq!(c.draw_state, DrawState::new_alpha());
+        assert!(c.viewport.is_none());
+    }
+
+    #[test]
+    fn test_view_rectangle() {
+        fn assert_rect(a: Option<[Scalar; 4]>, b: [Scalar; 4]) {
+            let a = a.unwrap();
+            for i in 0..4 {
+                assert!((a[i] - b[i]).abs() < 0.001);
+            }
+        }
+
+        let c = Context::new_abs(640.0, 480.0);
+        assert_rect(c.view_rectangle(), [0.0, 0.0, 640.0, 480.0]);
+        assert_rect(c.pan(10.0, 20.0).view_rectangle(), [-10.0, -20.0, 640.0, 480.0]);
+        assert_rect(c.zoom_view_at(2.0, [0.0, 0.0]).view_rectangle(),
+                    [0.0, 0.0, 320.0, 240.0]);
+        assert_rect(Context::new().view_rectangle(), [-1.0, -1.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn test_view_aspect_ratio() {
+        let c = Context::new_abs(1920.0, 1080.0);
+        assert!((c.view_aspect_ratio() - 16.0 / 9.0).abs() < 0.001);
+        assert!((Context::new().view_aspect_ratio() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_model() {
+        use Transformed;
+        use math::{identity, multiply, Matrix2d};
+
+        fn assert_near(a: Matrix2d, b: Matrix2d) {
+            for (a, b) in a.iter().zip(b.iter()) {
+                for (a, b) in a.iter().zip(b.iter()) {
+                    assert!((a - b).abs() < 0.0001 * b.abs().max(1.0), "{} != {}", a, b);
+                }
+            }
+        }
+
+        let c = Context::new_abs(640.0, 480.0);
+        assert_near(c.model().unwrap(), identity());
+
+        let d = c.trans(10.0, 20.0).rot_deg(30.0).zoom(2.0);
+        let m = d.model().unwrap();
+        assert_near(multiply(d.view, m), d.transform);
+        assert_near(m, identity().trans(10.0, 20.0).rot_deg(30.0).zoom(2.0));
+    }
+
+    #[test]
+    fn test_fit_view_to() {
+        use Transformed;
+
+        fn assert_pos(a: [Scalar; 2], b: [Scalar; 2]) {
+            assert!((a[0] - b[0]).abs() < 0.0001 && (a[1] - b[1]).abs() < 0.0001);
+        }
+
+        let c = Context::new_abs(640.0, 480.0);
+        let d = c.fit_view_to([100.0, 100.0, 400.0, 300.0], 0.0);
+        assert_pos(d.world_to_ndc([100.0, 100.0]), [-1.0, 1.0]);
+        assert_pos(d.world_to_ndc([500.0, 400.0]), [1.0, -1.0]);
+        assert_pos(transform_pos(d.transform, [100.0, 100.0]), [-1.0, 1.0]);
+        let g = c.trans(100.0, 100.0).fit_view_to([100.0, 100.0, 400.0, 300.0], 0.0);
+        assert_pos(transform_pos(g.transform, [0.0, 0.0]), [-1.0, 1.0]);
+
+        let e = c.fit_view_to([0.0, 0.0, 100.0, 300.0], 0.0);
+        assert_pos(e.world_to_ndc([0.0, 0.0]), [-0.25, 1.0]);
+        assert_pos(e.world_to_ndc([100.0, 300.0]), [0.25, -1.0]);
+
+        let f = c.fit_view_to([50.0, 50.0, 0.0, 0.0], 10.0);
+        assert_pos(f.world_to_ndc([40.0, 40.0]), [-0.75, 1.0]);
+        assert_pos(f.world_to_ndc([60.0, 60.0]), [0.75, -1.0]);
+        assert_eq!(c.fit_view_to([50.0, 50.0, 0.0, 0.0], 0.0).view, c.view);
+    }
+
+    #[test]
+    fn test_constrain_aspect() {
+        use super::AspectMode;
+        use Transformed;
+
+        fn assert_size(c: Context, w: Scalar, h: Scalar) {
+            let size = c.get_view_size();
+            assert!((size[0] - w).abs() < 0.001 && (size[1] - h).abs() < 0.001);
+            let center = c.ndc_to_world([0.0, 0.0]).unwrap();
+            assert!((center[0] - 1000.0).abs() < 0.001 && (center[1] - 500.0).abs() < 0.001);
+        }
+
+        let c = Context::new_abs(2000.0, 1000.0);
+        assert_size(c.constrain_aspect(1.5, AspectMode::Letterbox), 2000.0, 1000.0);
+        assert_size(c.constrain_aspect(1.5, AspectMode::Crop), 1500.0, 750.0);
+        assert_size(c.constrain_aspect(1.5, AspectMode::Stretch), 1500.0, 1000.0);
+        assert_size(c.constrain_aspect(4.0, AspectMode::Letterbox), 4000.0, 2000.0);
+        assert_size(c.constrain_aspect(4.0, AspectMode::Crop), 2000.0, 1000.0);
+        assert_size(c.constrain_aspect(2.0, AspectMode::Stretch), 2000.0, 1000.0);
+        assert_eq!(c.constrain_aspect(0.0, AspectMode::Crop).view, c.view);
+
+        let d = c.trans(1000.0, 500.0).constrain_aspect(1.5, AspectMode::Crop);
+        let p = transform_pos(d.transform, [0.0, 0.0]);
+        assert!(p[0].abs() < 0.0001 && p[1].abs() < 0.0001);
+        let q = transform_pos(d.transform, [750.0, 0.0]);
+        assert!((q[0] - 1.0).abs() < 0.0001 && q[1].abs() < 0.0001);
+    }
 }
@@ -40,7 +40,8 @@ pub use viewport::Viewport;
 pub use graphics::Graphics;
 pub use source_rectangled::SourceRectangled;
 pub use rectangled::Rectangled;
-pub use transformed::Transformed;
+pub use transformed::{TransformOp, Transformed};
+pub use transform_stack::TransformStack;
 pub use colored::Colored;
 pub use rectangle::Rectangle;
 pub use line::Line;
@@ -83,6 +84,7 @@ pub mod triangulation;
 pub mod math;
 pub mod deform;
 pub mod grid;
+pub mod transform_stack;
 pub mod glyph_cache;
 
 pub mod radians {
@@ -9,6 +9,7 @@ pub use vecmath::vec2_cast as cast;
 pub use vecmath::vec2_mul as mul;
 pub use vecmath::vec2_scale as mul_scalar;
 pub use vecmath::vec2_square_len as square_len;
+pub use vecmath::vec2_len as length;
 pub use vecmath::row_mat2x3_transform_pos2 as transform_pos;
 pub use vecmath::row_mat2x3_transform_vec2 as transform_vec;
 
@@ -19,8 +20,17 @@ use types::{Area, Color, Line, Polygon, Ray, Rectangle, SourceRectangle, Triangl
 use modular_index::previous;
 
 /// The type used for scalars.
+///
+/// This is `f64` by default, or `f32` with the `f32` feature.
+#[cfg(not(feature = "f32"))]
 pub type Scalar = f64;
 
+/// The type used for scalars.
+///
+/// This is `f64` by default, or `f32` with the `f32` feature.
+#[cfg(feature = "f32")]
+pub type Scalar = f32;
+
 /// The type used for matrices.
 pub type Matrix2d<T = Scalar> = vecmath::Matrix2x3<T>;
 
@@ -38,6 +48,53 @@ pub fn perp<T>(v: [T; 2]) -> [T; 2]
     [-v[1], v[0]]
 }
 
+/// Returns a vector with the same direction and length one.
+///
+/// Returns `None` for the zero vector.
+#[inline(always)]
+pub fn normalize<T>(v: Vec2d<T>) -> Option<Vec2d<T>>
+    where T: Float
+{
+    let len = length(v);
+    if len == T::zero() {
+        None
+    } else {
+        Some([v[0] / len, v[1] / len])
+    }
+}
+
+/// Computes the angle in radians that rotates the direction of `a` to `b`.
+///
+/// The angle is positive counter-clockwise, in the range [-pi, pi].
+/// Returns zero if either vector is zero.
+#[inline(always)]
+pub fn angle_between<T>(a: Vec2d<T>, b: Vec2d<T>) -> T
+    where T: Float
+{
+    cross(a, b).atan2(dot(a, b))
+}
+
+#[cfg(test)]
+mod test_vec {
+    use super::*;
+
+    #[test]
+    fn test_vec() {
+        assert_eq!(dot([1.0, 2.0], [3.0, 4.0]), 11.0);
+        assert_eq!(length([3.0, 4.0]), 5.0);
+        assert_eq!(normalize([3.0, 4.0]), Some([0.6, 0.8]));
+        assert_eq!(normalize([0.0, 0.0]), None);
+        assert_eq!(perp([1.0, 0.0]), [0.0, 1.0]);
+
+        let pi: f64 = ::std::f64::consts::PI;
+        assert!((angle_between([1.0, 0.0], [0.0, 1.0]) - 0.5 * pi).abs() < 1e-10);
+        assert!((angle_between([0.0, 1.0], [1.0, 0.0]) + 0.5 * pi).abs() < 1e-10);
+        assert!((angle_between([2.0, 2.0], [-1.0, 1.0]) - 0.5 * pi).abs() < 1e-10);
+        assert_eq!(angle_between([1.0, 0.0], [3.0, 0.0]), 0.0);
+        assert_eq!(angle_between([0.0, 0.0], [1.0, 0.0]), 0.0);
+    }
+}
+
 /// Transforms from normalized to absolute coordinates.
 ///
 /// Computes absolute transform from width and height of viewport.
@@ -139,6 +196,32 @@ pub fn identity<T>() -> Matrix2d<T>
     [[_1, _0, _0], [_0, _1, _0]]
 }
 
+/// The identity matrix, usable in constant expressions.
+///
+/// This is the same as `identity()` for `Scalar`.
+pub const IDENTITY: Matrix2d = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+
+#[cfg(test)]
+mod test_scalar {
+    use super::*;
+
+    #[test]
+    fn test_scalar_precision() {
+        let a: Matrix2d = multiply(multiply(translate([10.0, 20.0]), rotate_radians(0.5)),
+                                   scale(2.0, 3.0));
+        let b: Matrix2d<f64> = multiply(multiply(translate([10.0, 20.0]), rotate_radians(0.5)),
+                                        scale(2.0, 3.0));
+        // The tolerance follows the precision of `Scalar`.
+        let a: Matrix2d<f64> = vecmath::mat2x3_cast(a);
+        let epsilon: f64 = vecmath::traits::Cast::cast(8.0 * Scalar::EPSILON);
+        for (a, b) in a.iter().zip(b.iter()) {
+            for (&a, &b) in a.iter().zip(b.iter()) {
+                assert!((a - b).abs() <= epsilon * b.abs().max(1.0), "{} != {}", a, b);
+            }
+        }
+    }
+}
+
 /// Extract scale information from matrix.
 #[inline(always)]
 pub fn get_scale<T>(m: Matrix2d<T>) -> Vec2d<T>
@@ -147,6 +230,196 @@ pub fn get_scale<T>(m: Matrix2d<T>) -> Vec2d<T>
     [(m[0][0] * m[0][0] + m[1][0] * m[1][0]).sqrt(), (m[0][1] * m[0][1] + m[1][1] * m[1][1]).sqrt()]
 }
 
+/// Extract translation information from matrix.
+#[inline(always)]
+pub fn get_translation<T>(m: Matrix2d<T>) -> Vec2d<T>
+    where T: Float
+{
+    [m[0][2], m[1][2]]
+}
+
+/// Extract rotation information from matrix, in radians.
+///
+/// This is the angle of the transformed x axis.
+/// A matrix with shear can not be decomposed exactly,
+/// so the rotation of the y axis might differ.
+#[inline(always)]
+pub fn get_rotation<T>(m: Matrix2d<T>) -> T
+    where T: Float
+{
+    m[1][0].atan2(m[0][0])
+}
+
+/// Creates a matrix from translation, rotation in radians and scale.
+///
+/// The scale is applied first, then the rotation and then the translation,
+/// so this is `translate(t) * rotate_radians(r) * scale(s)`.
+/// For positive scale, this is the inverse of
+/// `get_translation`, `get_rotation` and `get_scale`.
+#[inline(always)]
+pub fn compose<T>(translation: Vec2d<T>, rotation: T, s: Vec2d<T>) -> Matrix2d<T>
+    where T: Float
+{
+    multiply(multiply(translate(translation), rotate_radians(rotation)), scale(s[0], s[1]))
+}
+
+/// Decomposes a matrix into translation, rotation in radians and scale.
+///
+/// This is the inverse of `compose` for matrices without shear,
+/// including matrices that flip.
+/// Unlike `get_scale`, the y scale is negative when the determinant is negative,
+/// so a flip is kept as a negative scale instead of being lost.
+#[inline(always)]
+pub fn decompose<T>(m: Matrix2d<T>) -> (Vec2d<T>, T, Vec2d<T>)
+    where T: Float
+{
+    let s = get_scale(m);
+    let sy = if vecmath::mat2x3_det(m) < T::zero() { -s[1] } else { s[1] };
+    (get_translation(m), get_rotation(m), [s[0], sy])
+}
+
+#[cfg(test)]
+mod test_compose {
+    use super::*;
+
+    #[test]
+    fn test_compose() {
+        let m: Matrix2d<f64> = compose([10.0, -20.0], 0.75, [2.0, 3.0]);
+        let t = get_translation(m);
+        let s = get_scale(m);
+        assert!((t[0] - 10.0).abs() < 1e-10 && (t[1] + 20.0).abs() < 1e-10);
+        assert!((get_rotation(m) - 0.75).abs() < 1e-10);
+        assert!((s[0] - 2.0).abs() < 1e-10 && (s[1] - 3.0).abs() < 1e-10);
+        let p = transform_pos(m, [1.0, 0.0]);
+        let q = transform_pos(translate([10.0, -20.0]),
+                              transform_pos(rotate_radians(0.75), [2.0, 0.0]));
+        assert!((p[0] - q[0]).abs() < 1e-10 && (p[1] - q[1]).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_decompose() {
+        let m: Matrix2d<f64> = compose([10.0, -20.0], 0.75, [2.0, -3.0]);
+        let (t, r, s) = decompose(m);
+        assert!((t[0] - 10.0).abs() < 1e-10 && (t[1] + 20.0).abs() < 1e-10);
+        assert!((r - 0.75).abs() < 1e-10);
+        assert!((s[0] - 2.0).abs() < 1e-10 && (s[1] + 3.0).abs() < 1e-10);
+        let n = compose(t, r, s);
+        for i in 0..2 {
+            for j in 0..3 {
+                assert!((m[i][j] - n[i][j]).abs() < 1e-10);
+            }
+        }
+        assert_eq!(decompose(scale(2.0, 3.0)), ([0.0, 0.0], 0.0, [2.0, 3.0]));
+    }
+}
+
+/// Computes the inverse of a matrix.
+///
+/// Returns `None` if the matrix is not invertible,
+/// which happens when the determinant is zero,
+/// or so small relative to the linear part that the columns are almost parallel.
+/// The absolute value of the determinant is compared against
+/// `f64::EPSILON * (|m00| + |m01|) * (|m10| + |m11|)`,
+/// so uniformly tiny or huge scales are still invertible.
+#[inline(always)]
+pub fn invert<T>(m: Matrix2d<T>) -> Option<Matrix2d<T>>
+    where T: Float
+{
+    use vecmath::traits::FromPrimitive;
+
+    let abs = |x: T| if x < T::zero() { -x } else { x };
+    let det = vecmath::mat2x3_det(m);
+    let epsilon: T = FromPrimitive::from_f64(f64::EPSILON);
+    let bound = epsilon * (abs(m[0][0]) + abs(m[0][1])) * (abs(m[1][0]) + abs(m[1][1]));
+    if abs(det) <= bound {
+        None
+    } else {
+        Some(vecmath::mat2x3_inv(m))
+    }
+}
+
+/// Formats a matrix as two rows with aligned columns.
+///
+/// Each component is printed with three decimals, for debugging transforms.
+pub fn debug_matrix(m: Matrix2d) -> String {
+    let cells: Vec<String> = m.iter()
+        .flat_map(|row| row.iter())
+        .map(|v| format!("{:.3}", v))
+        .collect();
+    let width = cells.iter().map(|c| c.len()).max().unwrap_or(0);
+    cells.chunks(3)
+        .map(|row| {
+            let row: Vec<String> = row.iter().map(|c| format!("{:>1$}", c, width)).collect();
+            format!("[{}]", row.join(", "))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test_debug_matrix {
+    use super::*;
+
+    #[test]
+    fn test_debug_matrix() {
+        let m = [[1.0, 0.0, 10.0], [0.0, 2.0, -2.5]];
+        assert_eq!(debug_matrix(m),
+                   "[ 1.000,  0.000, 10.000]\n[ 0.000,  2.000, -2.500]");
+    }
+}
+
+/// Converts a matrix to a 3x3 matrix in column major order.
+///
+/// `Matrix2d` is row major, `[[a, b, c], [d, e, f]]`,
+/// which becomes `[a, d, 0, b, e, 0, c, f, 1]`.
+/// The translation ends up in elements 6 and 7, as expected by OpenGL style uniforms.
+pub fn to_gl_mat3(m: Matrix2d) -> [f32; 9] {
+    let m: Matrix2d<f32> = vecmath::mat2x3_cast(m);
+    [m[0][0], m[1][0], 0.0,
+     m[0][1], m[1][1], 0.0,
+     m[0][2], m[1][2], 1.0]
+}
+
+/// Converts a matrix to a 4x4 matrix in column major order.
+///
+/// `Matrix2d` is row major, `[[a, b, c], [d, e, f]]`,
+/// which becomes `[a, d, 0, 0, b, e, 0, 0, 0, 0, 1, 0, c, f, 0, 1]`.
+/// The z axis is left unchanged and the translation ends up in elements 12 and 13.
+pub fn to_gl_mat4(m: Matrix2d) -> [f32; 16] {
+    let m: Matrix2d<f32> = vecmath::mat2x3_cast(m);
+    [m[0][0], m[1][0], 0.0, 0.0,
+     m[0][1], m[1][1], 0.0, 0.0,
+     0.0, 0.0, 1.0, 0.0,
+     m[0][2], m[1][2], 0.0, 1.0]
+}
+
+/// Converts from a 3x3 matrix in column major order.
+///
+/// This is the inverse of `to_gl_mat3`. The last row is ignored.
+pub fn from_gl_mat3(m: [f32; 9]) -> Matrix2d {
+    vecmath::mat2x3_cast([[m[0], m[3], m[6]], [m[1], m[4], m[7]]])
+}
+
+#[cfg(test)]
+mod test_gl_mat {
+    use super::*;
+
+    #[test]
+    fn test_gl_mat() {
+        let m = translate([3.0, 4.0]);
+        assert_eq!(to_gl_mat3(m), [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 3.0, 4.0, 1.0]);
+        let m4 = to_gl_mat4(m);
+        assert_eq!((m4[12], m4[13], m4[15]), (3.0, 4.0, 1.0));
+        assert_eq!(&m4[0..4], &[1.0, 0.0, 0.0, 0.0]);
+
+        let m = [[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+        assert_eq!(to_gl_mat3(m), [1.0, 4.0, 0.0, 2.0, 5.0, 0.0, 3.0, 6.0, 1.0]);
+        assert_eq!(to_gl_mat4(m), [1.0, 4.0, 0.0, 0.0, 2.0, 5.0, 0.0, 0.0,
+                                   0.0, 0.0, 1.0, 0.0, 3.0, 6.0, 0.0, 1.0]);
+        assert_eq!(from_gl_mat3(to_gl_mat3(m)), m);
+    }
+}
+
 /// Compute the shortest vector from point to ray.
 /// A ray stores starting point and directional vector.
 #[inline(always)]
@@ -224,6 +497,30 @@ pub fn margin_rectangle<T>(rect: Rectangle<T>, m: T) -> Rectangle<T>
     [x, y, w, h]
 }
 
+/// Shrinks a rectangle by different amounts on each side.
+///
+/// When the width or height would become negative,
+/// it is clamped to zero at the center of the rectangle,
+/// the same way as `margin_rectangle`.
+#[inline(always)]
+pub fn padding_rectangle<T>(rect: Rectangle<T>, top: T, right: T, bottom: T, left: T) -> Rectangle<T>
+    where T: Float
+{
+    let w = rect[2] - left - right;
+    let h = rect[3] - top - bottom;
+    let (x, w) = if w < T::zero() {
+        (rect[0] + T::from_f64(0.5) * rect[2], T::zero())
+    } else {
+        (rect[0] + left, w)
+    };
+    let (y, h) = if h < T::zero() {
+        (rect[1] + T::from_f64(0.5) * rect[3], T::zero())
+    } else {
+        (rect[1] + top, h)
+    };
+    [x, y, w, h]
+}
+
 /// Computes a relative rectangle using the rectangle as a tile.
 #[inline(always)]
 pub fn relative_rectangle<T>(rect: Rectangle<T>, v: Vec2d<T>) -> Rectangle<T>
@@ -232,6 +529,35 @@ pub fn relative_rectangle<T>(rect: Rectangle<T>, v: Vec2d<T>) -> Rectangle<T>
     [rect[0] + v[0] * rect[2], rect[1] + v[1] * rect[3], rect[2], rect[3]]
 }
 
+/// Computes the smallest rectangle that contains all points.
+///
+/// Returns `None` if there are no points.
+/// A single point gives a rectangle with zero width and height.
+pub fn bounding_rectangle<T>(points: &[Vec2d<T>]) -> Option<Rectangle<T>>
+    where T: Float
+{
+    let (first, rest) = points.split_first()?;
+    let (mut min, mut max) = (*first, *first);
+    for p in rest {
+        min = [min[0].min(p[0]), min[1].min(p[1])];
+        max = [max[0].max(p[0]), max[1].max(p[1])];
+    }
+    Some([min[0], min[1], max[0] - min[0], max[1] - min[1]])
+}
+
+#[cfg(test)]
+mod test_bounding_rectangle {
+    use super::*;
+
+    #[test]
+    fn test_bounding_rectangle() {
+        let tri = [[1.0, 5.0], [4.0, -2.0], [-3.0, 1.0]];
+        assert_eq!(bounding_rectangle(&tri), Some([-3.0, -2.0, 7.0, 7.0]));
+        assert_eq!(bounding_rectangle(&[[2.0, 3.0]]), Some([2.0, 3.0, 0.0, 0.0]));
+        assert_eq!(bounding_rectangle::<f64>(&[]), None);
+    }
+}
+
 /// Computes overlap between two rectangles.
 /// The area of the overlapping rectangle is positive.
 /// A shared edge or corner is not considered overlap.
@@ -14,8 +14,8 @@
 //! ```
 
 use types::{Color, Radius, Resolution};
-use {types, triangulation, Graphics, DrawState};
-use math::{Matrix2d, Scalar};
+use {types, triangulation, Graphics, DrawState, Rectangled};
+use math::{Matrix2d, Scalar, Vec2d};
 
 pub use math::margin_rectangle as margin;
 
@@ -39,6 +39,19 @@ pub fn rectangle_by_corners(x0: Scalar, y0: Scalar, x1: Scalar, y1: Scalar) -> t
     [xmin, ymin, w, h]
 }
 
+/// Create `types::Rectangle` by the two opposite corners.
+///
+/// The corners can be given in any order,
+/// and width and height are never negative.
+pub fn rectangle_from_corners(a: Vec2d, b: Vec2d) -> types::Rectangle {
+    rectangle_by_corners(a[0], a[1], b[0], b[1])
+}
+
+/// Create `types::Rectangle` by a center and half the width and height.
+pub fn rectangle_centered(center: Vec2d, half: Vec2d) -> types::Rectangle {
+    centered([center[0], center[1], half[0], half[1]])
+}
+
 /// Use x, y, half-width, half-height
 pub fn centered(rect: types::Rectangle) -> types::Rectangle {
     [rect[0] - rect[2], rect[1] - rect[3], 2.0 * rect[2], 2.0 * rect[3]]
@@ -56,6 +69,84 @@ pub fn square(x: Scalar, y: Scalar, size: Scalar) -> types::Rectangle {
     [x, y, size, size]
 }
 
+/// Rectangle dimensions with named fields.
+///
+/// This converts to and from `types::Rectangle`,
+/// so it can be passed anywhere a rectangle is drawn.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rect {
+    /// The x coordinate of the upper left corner.
+    pub x: Scalar,
+    /// The y coordinate of the upper left corner.
+    pub y: Scalar,
+    /// The width.
+    pub w: Scalar,
+    /// The height.
+    pub h: Scalar,
+}
+
+impl Rect {
+    /// Returns the center.
+    #[inline(always)]
+    pub fn center(&self) -> Vec2d {
+        types::Rectangle::from(*self).center()
+    }
+
+    /// Returns the width and height.
+    #[inline(always)]
+    pub fn size(&self) -> Vec2d {
+        types::Rectangle::from(*self).size()
+    }
+
+    /// Returns true if a point is inside.
+    ///
+    /// See `Rectangled::contains`.
+    #[inline(always)]
+    pub fn contains(&self, point: Vec2d) -> bool {
+        types::Rectangle::from(*self).contains(point)
+    }
+
+    /// Makes width and height non-negative, covering the same region.
+    #[inline(always)]
+    pub fn normalize(self) -> Rect {
+        types::Rectangle::from(self).normalize().into()
+    }
+}
+
+impl From<types::Rectangle> for Rect {
+    #[inline(always)]
+    fn from(rect: types::Rectangle) -> Rect {
+        Rect {
+            x: rect[0],
+            y: rect[1],
+            w: rect[2],
+            h: rect[3],
+        }
+    }
+}
+
+impl From<Rect> for types::Rectangle {
+    #[inline(always)]
+    fn from(rect: Rect) -> types::Rectangle {
+        [rect.x, rect.y, rect.w, rect.h]
+    }
+}
+
+/// An edge of a rectangle.
+///
+/// The upper edge is `Top`, as seen on the screen when the y axis points down.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Edge {
+    /// The upper edge.
+    Top,
+    /// The right edge.
+    Right,
+    /// The lower edge.
+    Bottom,
+    /// The left edge.
+    Left,
+}
+
 /// The shape of the rectangle corners
 #[derive(Copy, Clone)]
 pub enum Shape {
@@ -274,4 +365,28 @@ mod test {
         assert_eq!(rectangle_by_corners(2.0, 3.0, 1.0, -1.0),
                    [1.0, -1.0, 1.0, 4.0]);
     }
+
+    #[test]
+    fn test_rectangle_from_corners() {
+        assert_eq!(rectangle_from_corners([1.0, -1.0], [2.0, 3.0]), [1.0, -1.0, 1.0, 4.0]);
+        assert_eq!(rectangle_from_corners([2.0, 3.0], [1.0, -1.0]), [1.0, -1.0, 1.0, 4.0]);
+        assert_eq!(rectangle_from_corners([2.0, -1.0], [1.0, 3.0]), [1.0, -1.0, 1.0, 4.0]);
+    }
+
+    #[test]
+    fn test_rectangle_centered() {
+        assert_eq!(rectangle_centered([5.0, 5.0], [2.0, 3.0]), [3.0, 2.0, 4.0, 6.0]);
+    }
+
+    #[test]
+    fn test_rect() {
+        let a = [10.0, 20.0, -5.0, 6.0];
+        let rect = Rect::from(a);
+        assert_eq!(rect, Rect { x: 10.0, y: 20.0, w: -5.0, h: 6.0 });
+        assert_eq!(types::Rectangle::from(rect), a);
+        assert_eq!(rect.center(), [7.5, 23.0]);
+        assert_eq!(rect.size(), [-5.0, 6.0]);
+        assert!(rect.contains([7.0, 21.0]));
+        assert_eq!(rect.normalize(), Rect { x: 5.0, y: 20.0, w: 5.0, h: 6.0 });
+    }
 }
@@ -1,10 +1,29 @@
-use math::{margin_rectangle, relative_rectangle, Scalar};
+use math::{bounding_rectangle, margin_rectangle, overlap_rectangle, padding_rectangle,
+           relative_rectangle, Scalar, Vec2d};
+use rectangle::{rectangle_by_corners, rectangle_centered, rectangle_from_corners};
 use types::Rectangle;
 
 /// Should be implemented by contexts that have rectangle information.
 pub trait Rectangled: Sized {
+    /// Returns the current rectangle.
+    fn get_rectangle(&self) -> Rectangle;
+
+    /// Replaces the current rectangle in place.
+    fn set_rectangle(&mut self, rect: Rectangle);
+
+    /// Replaces the current rectangle.
+    #[inline(always)]
+    fn with_rectangle(mut self, rect: Rectangle) -> Self {
+        self.set_rectangle(rect);
+        self
+    }
+
     /// Shrinks the current rectangle equally by all sides.
-    fn margin(self, m: Scalar) -> Self;
+    #[inline(always)]
+    fn margin(self, m: Scalar) -> Self {
+        let r = margin_rectangle(self.get_rectangle(), m);
+        self.with_rectangle(r)
+    }
 
     /// Expands the current rectangle equally by all sides.
     #[inline(always)]
@@ -13,17 +32,496 @@ pub trait Rectangled: Sized {
     }
 
     /// Moves to a relative rectangle using the current rectangle as tile.
-    fn rel(self, x: Scalar, y: Scalar) -> Self;
+    #[inline(always)]
+    fn rel(self, x: Scalar, y: Scalar) -> Self {
+        let r = relative_rectangle(self.get_rectangle(), [x, y]);
+        self.with_rectangle(r)
+    }
+
+    /// Shrinks the current rectangle by different amounts on each side.
+    ///
+    /// Width and height are clamped to zero, like `margin`.
+    #[inline(always)]
+    fn padding(self, top: Scalar, right: Scalar, bottom: Scalar, left: Scalar) -> Self {
+        let r = padding_rectangle(self.get_rectangle(), top, right, bottom, left);
+        self.with_rectangle(r)
+    }
+
+    /// Returns the center of the current rectangle.
+    #[inline(always)]
+    fn center(&self) -> Vec2d {
+        let r = self.get_rectangle();
+        [r[0] + 0.5 * r[2], r[1] + 0.5 * r[3]]
+    }
+
+    /// Returns the width and height of the current rectangle.
+    #[inline(always)]
+    fn size(&self) -> Vec2d {
+        let r = self.get_rectangle();
+        [r[2], r[3]]
+    }
+
+    /// Fits a centered rectangle with an aspect ratio (width / height)
+    /// inside the current rectangle, as large as possible.
+    #[inline(always)]
+    fn fit_aspect(self, aspect: Scalar) -> Self {
+        let r = self.get_rectangle();
+        let (w, h) = if r[2] > r[3] * aspect {
+            (r[3] * aspect, r[3])
+        } else {
+            (r[2], r[2] / aspect)
+        };
+        self.with_rectangle([r[0] + 0.5 * (r[2] - w), r[1] + 0.5 * (r[3] - h), w, h])
+    }
+
+    /// Scales width and height of the current rectangle, keeping the center fixed.
+    #[inline(always)]
+    fn scale_from_center(self, sx: Scalar, sy: Scalar) -> Self {
+        let r = self.get_rectangle();
+        self.with_rectangle([r[0] + 0.5 * r[2] * (1.0 - sx),
+                             r[1] + 0.5 * r[3] * (1.0 - sy),
+                             r[2] * sx,
+                             r[3] * sy])
+    }
+
+    /// Scales the current rectangle equally in both directions, keeping the center fixed.
+    #[inline(always)]
+    fn zoom_rect(self, s: Scalar) -> Self {
+        self.scale_from_center(s, s)
+    }
+
+    /// Returns a cell of the current rectangle divided into a uniform grid.
+    ///
+    /// A `col` or `row` outside the grid does not panic,
+    /// but produces a cell of the same size outside the current rectangle.
+    /// Zero `cols` or `rows` does not panic either,
+    /// but gives an infinite width or height and an infinite or NaN position.
+    #[inline(always)]
+    fn grid_cell(self, cols: u32, rows: u32, col: u32, row: u32) -> Self {
+        let r = self.get_rectangle();
+        let w = r[2] / cols as Scalar;
+        let h = r[3] / rows as Scalar;
+        self.with_rectangle([r[0] + col as Scalar * w, r[1] + row as Scalar * h, w, h])
+    }
+
+    /// Returns true if a point is inside the current rectangle.
+    ///
+    /// Points on the edges are inside.
+    /// A rectangle with zero width or height contains no points.
+    /// Negative width or height is normalized first.
+    #[inline(always)]
+    fn contains(&self, point: Vec2d) -> bool {
+        let r = self.get_rectangle().normalize();
+        r[2] != 0.0 && r[3] != 0.0 &&
+        point[0] >= r[0] && point[0] <= r[0] + r[2] &&
+        point[1] >= r[1] && point[1] <= r[1] + r[3]
+    }
+
+    /// Returns true if the current rectangle overlaps with another rectangle.
+    ///
+    /// A shared edge or corner is not considered overlap.
+    /// Negative width or height is normalized first.
+    #[inline(always)]
+    fn intersects(&self, other: Rectangle) -> bool {
+        overlap_rectangle(self.get_rectangle().normalize(), other.normalize()).is_some()
+    }
+
+    /// Moves to a relative rectangle using the current rectangle as tile,
+    /// measured from an anchor point instead of the upper left corner.
+    ///
+    /// The anchor is in tile coordinates, where `[0.0, 0.0]` is the upper left corner
+    /// and `[0.5, 0.5]` is the center.
+    /// The anchor of the new rectangle is placed where `rel` would place the upper left corner.
+    #[inline(always)]
+    fn rel_with_anchor(self, x: Scalar, y: Scalar, anchor: Vec2d) -> Self {
+        let r = relative_rectangle(self.get_rectangle(), [x - anchor[0], y - anchor[1]]);
+        self.with_rectangle(r)
+    }
+
+    /// Sets the current rectangle by two opposite corners.
+    ///
+    /// The corners can be given in any order,
+    /// and width and height are never negative.
+    #[inline(always)]
+    fn set_corners(self, a: Vec2d, b: Vec2d) -> Self {
+        self.with_rectangle(rectangle_from_corners(a, b))
+    }
+
+    /// Sets the current rectangle by a center and half the width and height.
+    #[inline(always)]
+    fn set_centered(self, center: Vec2d, half: Vec2d) -> Self {
+        self.with_rectangle(rectangle_centered(center, half))
+    }
+
+    /// Makes width and height of the current rectangle non-negative,
+    /// covering the same region.
+    #[inline(always)]
+    fn normalize(self) -> Self {
+        let r = self.get_rectangle();
+        self.with_rectangle(rectangle_by_corners(r[0], r[1], r[0] + r[2], r[1] + r[3]))
+    }
+
+    /// Returns the smallest rectangle containing both the current and another rectangle.
+    ///
+    /// Negative width or height is normalized first.
+    #[inline(always)]
+    fn union(self, other: Rectangle) -> Self {
+        let (a, b) = (self.get_rectangle().normalize(), other.normalize());
+        self.with_rectangle(rectangle_by_corners(a[0].min(b[0]),
+                                                 a[1].min(b[1]),
+                                                 (a[0] + a[2]).max(b[0] + b[2]),
+                                                 (a[1] + a[3]).max(b[1] + b[3])))
+    }
+
+    /// Returns the overlap of the current and another rectangle.
+    ///
+    /// Returns `None` when the rectangles only share an edge or corner,
+    /// or do not overlap at all.
+    /// Negative width or height is normalized first.
+    #[inline(always)]
+    fn intersect(self, other: Rectangle) -> Option<Self> {
+        overlap_rectangle(self.get_rectangle().normalize(), other.normalize())
+            .map(|r| self.with_rectangle(r))
+    }
+
+    /// Clamps a point to lie inside the rectangle.
+    ///
+    /// The rectangle is normalized first.
+    /// A rectangle with zero area collapses the point onto its edge or position.
+    #[inline(always)]
+    fn clamp_point(&self, point: Vec2d) -> Vec2d {
+        let r = self.get_rectangle().normalize();
+        [point[0].max(r[0]).min(r[0] + r[2]), point[1].max(r[1]).min(r[1] + r[3])]
+    }
+
+    /// Returns the point at an anchor inside the rectangle.
+    ///
+    /// The anchor is relative to the size, where `[0.0, 0.0]` is the upper left corner,
+    /// `[1.0, 1.0]` is the lower right corner and `[0.5, 0.5]` is the center.
+    #[inline(always)]
+    fn anchor_point(&self, anchor: Vec2d) -> Vec2d {
+        let r = self.get_rectangle();
+        [r[0] + anchor[0] * r[2], r[1] + anchor[1] * r[3]]
+    }
+
+    /// Sets the rectangle to the smallest one that contains all points.
+    ///
+    /// Leaves the rectangle unchanged if there are no points.
+    #[inline(always)]
+    fn fit_points(self, points: &[Vec2d]) -> Self {
+        match bounding_rectangle(points) {
+            Some(r) => self.with_rectangle(r),
+            None => self,
+        }
+    }
+
+    /// Shrinks the current rectangle by a fraction of its size.
+    ///
+    /// Each side moves in by `fx` of the width and `fy` of the height,
+    /// so `0.5` or more collapses the rectangle at its center.
+    /// Width and height are clamped to zero, like `margin`.
+    #[inline(always)]
+    fn margin_ratio(self, fx: Scalar, fy: Scalar) -> Self {
+        let r = self.get_rectangle();
+        let (x, y) = (fx * r[2], fy * r[3]);
+        self.with_rectangle(padding_rectangle(r, y, x, y, x))
+    }
+
+    /// Shrinks the current rectangle by the same fraction of its width and height.
+    #[inline(always)]
+    fn margin_ratio_uniform(self, f: Scalar) -> Self {
+        self.margin_ratio(f, f)
+    }
+
+    /// Linearly interpolates every component from the current rectangle to a target rectangle.
+    ///
+    /// 0 is the current rectangle and 1 is the target rectangle.
+    /// Factors outside [0, 1] extrapolate.
+    #[inline(always)]
+    fn lerp_rect(self, target: Rectangle, t: Scalar) -> Self {
+        let r = self.get_rectangle();
+        let u = 1.0 - t;
+        self.with_rectangle([r[0] * u + target[0] * t,
+                             r[1] * u + target[1] * t,
+                             r[2] * u + target[2] * t,
+                             r[3] * u + target[3] * t])
+    }
+
+    /// Returns the aspect ratio (width / height) of the current rectangle.
+    ///
+    /// Returns zero if the height is zero,
+    /// so a degenerate rectangle never gives infinity or NaN.
+    #[inline(always)]
+    fn aspect_ratio(&self) -> Scalar {
+        let r = self.get_rectangle();
+        if r[3] == 0.0 { 0.0 } else { r[2] / r[3] }
+    }
+
+    /// Wraps a point around to lie inside the rectangle, for looping worlds.
+    ///
+    /// The rectangle is normalized first, and the right and lower edges
+    /// wrap to the left and upper edges.
+    /// When the width or height is zero, that coordinate collapses onto the edge,
+    /// like `clamp_point`.
+    #[inline(always)]
+    fn wrap_point(&self, point: Vec2d) -> Vec2d {
+        fn wrap(v: Scalar, start: Scalar, size: Scalar) -> Scalar {
+            if size == 0.0 { start } else { start + (v - start).rem_euclid(size) }
+        }
+
+        let r = self.get_rectangle().normalize();
+        [wrap(point[0], r[0], r[2]), wrap(point[1], r[1], r[3])]
+    }
+
+    /// Returns the four corners of the current rectangle.
+    ///
+    /// The order is upper left, upper right, lower right and lower left,
+    /// which is clockwise on the screen when the y axis points down.
+    /// The rectangle is not normalized, so negative width or height reverse the order.
+    #[inline(always)]
+    fn corners(&self) -> [Vec2d; 4] {
+        let r = self.get_rectangle();
+        let (x, y, w, h) = (r[0], r[1], r[2], r[3]);
+        [[x, y], [x + w, y], [x + w, y + h], [x, y + h]]
+    }
 }
 
 impl Rectangled for Rectangle {
     #[inline(always)]
-    fn margin(self, m: Scalar) -> Self {
-        margin_rectangle(self, m)
+    fn get_rectangle(&self) -> Rectangle {
+        *self
     }
 
     #[inline(always)]
-    fn rel(self, x: Scalar, y: Scalar) -> Self {
-        relative_rectangle(self, [x, y])
+    fn set_rectangle(&mut self, rect: Rectangle) {
+        *self = rect;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_accessors() {
+        struct Button {
+            rect: Rectangle,
+        }
+
+        impl Rectangled for Button {
+            fn get_rectangle(&self) -> Rectangle {
+                self.rect
+            }
+
+            fn set_rectangle(&mut self, rect: Rectangle) {
+                self.rect = rect;
+            }
+        }
+
+        let a: Rectangle = [10.0, 20.0, 30.0, 40.0];
+        let b = Button { rect: a };
+        assert_eq!(b.center(), a.center());
+        assert!(b.contains([15.0, 25.0]));
+        assert_eq!(b.margin(5.0).rect, a.margin(5.0));
+        let b = Button { rect: a };
+        assert_eq!(b.intersect([0.0, 0.0, 20.0, 30.0]).unwrap().rect, [10.0, 20.0, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn test_padding() {
+        let rect: Rectangle = [10.0, 20.0, 100.0, 50.0];
+        assert_eq!(rect.padding(5.0, 5.0, 5.0, 5.0), rect.margin(5.0));
+        assert_eq!(rect.padding(40.0, 40.0, 40.0, 40.0), rect.margin(40.0));
+        assert_eq!(rect.padding(1.0, 2.0, 3.0, 4.0), [14.0, 21.0, 94.0, 46.0]);
+    }
+
+    #[test]
+    fn test_center_size() {
+        let a: Rectangle = [10.0, 20.0, 100.0, 50.0];
+        assert_eq!(a.center(), [60.0, 45.0]);
+        assert_eq!(a.size(), [100.0, 50.0]);
+        let b: Rectangle = [10.0, 20.0, -100.0, -50.0];
+        assert_eq!(b.center(), [-40.0, -5.0]);
+        assert_eq!(b.size(), [-100.0, -50.0]);
+    }
+
+    #[test]
+    fn test_fit_aspect() {
+        let wide: Rectangle = [0.0, 0.0, 200.0, 100.0];
+        assert_eq!(wide.fit_aspect(1.0), [50.0, 0.0, 100.0, 100.0]);
+        let tall: Rectangle = [0.0, 0.0, 100.0, 200.0];
+        assert_eq!(tall.fit_aspect(1.0), [0.0, 50.0, 100.0, 100.0]);
+        let same = wide.fit_aspect(2.0);
+        for i in 0..4 {
+            assert!((same[i] - wide[i]).abs() < 0.00001);
+        }
+    }
+
+    #[test]
+    fn test_scale_from_center() {
+        let a: Rectangle = [10.0, 20.0, 30.0, 70.0];
+        assert_eq!(a.scale_from_center(1.0, 1.0), a);
+        assert_eq!(a.zoom_rect(2.0).center(), a.center());
+        assert_eq!(a.zoom_rect(2.0), [-5.0, -15.0, 60.0, 140.0]);
+        assert_eq!(a.scale_from_center(0.5, 3.0).center(), a.center());
+    }
+
+    #[test]
+    fn test_grid_cell() {
+        let a: Rectangle = [10.0, 20.0, 100.0, 50.0];
+        assert_eq!(a.grid_cell(2, 2, 0, 0), [10.0, 20.0, 50.0, 25.0]);
+        assert_eq!(a.grid_cell(2, 2, 1, 0), [60.0, 20.0, 50.0, 25.0]);
+        assert_eq!(a.grid_cell(2, 2, 0, 1), [10.0, 45.0, 50.0, 25.0]);
+        assert_eq!(a.grid_cell(2, 2, 1, 1), [60.0, 45.0, 50.0, 25.0]);
+        assert_eq!(a.grid_cell(2, 2, 2, 0), a.grid_cell(2, 2, 1, 0).rel(1.0, 0.0));
+    }
+
+    #[test]
+    fn test_contains() {
+        let a: Rectangle = [0.0, 0.0, 10.0, 10.0];
+        assert!(a.contains([5.0, 5.0]));
+        assert!(a.contains([10.0, 0.0]));
+        assert!(!a.contains([10.1, 5.0]));
+        assert!(!a.contains([-1.0, 5.0]));
+        let b: Rectangle = [10.0, 10.0, -10.0, -10.0];
+        assert!(b.contains([5.0, 5.0]));
+        let c: Rectangle = [0.0, 0.0, 10.0, 0.0];
+        assert!(!c.contains([5.0, 0.0]));
+    }
+
+    #[test]
+    fn test_intersects() {
+        let a: Rectangle = [0.0, 0.0, 10.0, 10.0];
+        assert!(a.intersects([5.0, 5.0, 10.0, 10.0]));
+        assert!(a.intersects([15.0, 15.0, -10.0, -10.0]));
+        assert!(!a.intersects([10.0, 0.0, 10.0, 10.0]));
+        assert!(!a.intersects([20.0, 20.0, 10.0, 10.0]));
+    }
+
+    #[test]
+    fn test_rel_with_anchor() {
+        let a: Rectangle = [0.0, 0.0, 10.0, 20.0];
+        assert_eq!(a.rel_with_anchor(1.0, 2.0, [0.0, 0.0]), a.rel(1.0, 2.0));
+        let b = a.rel_with_anchor(1.0, 0.0, [0.5, 0.5]);
+        assert_eq!(b, [5.0, -10.0, 10.0, 20.0]);
+        assert_eq!(b.center(), a.rel(1.0, 0.0)[..2]);
+    }
+
+    #[test]
+    fn test_set_corners() {
+        let a: Rectangle = [0.0; 4];
+        assert_eq!(a.set_corners([10.0, 20.0], [0.0, 0.0]), [0.0, 0.0, 10.0, 20.0]);
+        assert_eq!(a.set_centered([10.0, 20.0], [1.0, 2.0]), [9.0, 18.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let a: Rectangle = [10.0, 20.0, 30.0, 40.0];
+        assert_eq!(a.normalize(), a);
+        let b: Rectangle = [40.0, 60.0, -30.0, -40.0];
+        assert_eq!(b.normalize(), a);
+        assert_eq!([10.0, 60.0, 30.0, -40.0].normalize(), a);
+    }
+
+    #[test]
+    fn test_union_intersect() {
+        let a: Rectangle = [0.0, 0.0, 10.0, 10.0];
+        let b: Rectangle = [15.0, 15.0, -10.0, -10.0];
+        assert_eq!(a.union(b), [0.0, 0.0, 15.0, 15.0]);
+        assert_eq!(a.intersect(b), Some([5.0, 5.0, 5.0, 5.0]));
+        let c: Rectangle = [10.0, 0.0, 10.0, 10.0];
+        assert_eq!(a.union(c), [0.0, 0.0, 20.0, 10.0]);
+        assert_eq!(a.intersect(c), None);
+        let d: Rectangle = [20.0, 30.0, 10.0, 10.0];
+        assert_eq!(a.union(d), [0.0, 0.0, 30.0, 40.0]);
+        assert_eq!(a.intersect(d), None);
+    }
+
+    #[test]
+    fn test_clamp_point() {
+        let a: Rectangle = [10.0, 20.0, 30.0, 40.0];
+        assert_eq!(a.clamp_point([15.0, 25.0]), [15.0, 25.0]);
+        assert_eq!(a.clamp_point([0.0, 25.0]), [10.0, 25.0]);
+        assert_eq!(a.clamp_point([50.0, 25.0]), [40.0, 25.0]);
+        assert_eq!(a.clamp_point([15.0, 0.0]), [15.0, 20.0]);
+        assert_eq!(a.clamp_point([15.0, 70.0]), [15.0, 60.0]);
+        assert_eq!(a.clamp_point([10.0, 60.0]), [10.0, 60.0]);
+        assert_eq!(a.clamp_point([-5.0, 100.0]), [10.0, 60.0]);
+        let b: Rectangle = [40.0, 60.0, -30.0, -40.0];
+        assert_eq!(b.clamp_point([0.0, 100.0]), [10.0, 60.0]);
+        let c: Rectangle = [5.0, 5.0, 0.0, 0.0];
+        assert_eq!(c.clamp_point([1.0, 9.0]), [5.0, 5.0]);
+    }
+
+    #[test]
+    fn test_anchor_point() {
+        let a: Rectangle = [10.0, 20.0, 30.0, 40.0];
+        assert_eq!(a.anchor_point([0.0, 0.0]), [10.0, 20.0]);
+        assert_eq!(a.anchor_point([1.0, 0.0]), [40.0, 20.0]);
+        assert_eq!(a.anchor_point([0.0, 1.0]), [10.0, 60.0]);
+        assert_eq!(a.anchor_point([1.0, 1.0]), [40.0, 60.0]);
+        assert_eq!(a.anchor_point([0.5, 0.5]), a.center());
+    }
+
+    #[test]
+    fn test_fit_points() {
+        let a: Rectangle = [0.0, 0.0, 1.0, 1.0];
+        assert_eq!(a.fit_points(&[[1.0, 5.0], [4.0, -2.0], [-3.0, 1.0]]), [-3.0, -2.0, 7.0, 7.0]);
+        assert_eq!(a.fit_points(&[[2.0, 3.0]]), [2.0, 3.0, 0.0, 0.0]);
+        assert_eq!(a.fit_points(&[]), a);
+    }
+
+    #[test]
+    fn test_margin_ratio() {
+        let a: Rectangle = [0.0, 0.0, 100.0, 100.0];
+        assert_eq!(a.margin_ratio(0.1, 0.1), [10.0, 10.0, 80.0, 80.0]);
+        assert_eq!(a.margin_ratio(0.25, 0.0), [25.0, 0.0, 50.0, 100.0]);
+        assert_eq!(a.margin_ratio_uniform(0.1), a.margin_ratio(0.1, 0.1));
+        assert_eq!(a.margin_ratio_uniform(0.6), [50.0, 50.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_lerp_rect() {
+        let a: Rectangle = [0.1, 0.2, 10.0, 20.0];
+        let b = [100.3, 50.7, 30.0, 40.0];
+        assert_eq!(a.lerp_rect(b, 0.0), a);
+        assert_eq!(a.lerp_rect(b, 1.0), b);
+        let c: Rectangle = [0.0, 0.0, 10.0, 20.0];
+        assert_eq!(c.lerp_rect([100.0, 50.0, 30.0, 40.0], 0.5), [50.0, 25.0, 20.0, 30.0]);
+    }
+
+    #[test]
+    fn test_aspect_ratio() {
+        let a: Rectangle = [10.0, 10.0, 1920.0, 1080.0];
+        assert!((a.aspect_ratio() - 16.0 / 9.0).abs() < 0.00001);
+        assert_eq!([0.0, 0.0, 100.0, 0.0].aspect_ratio(), 0.0);
+        assert_eq!([0.0, 0.0, 0.0, 0.0].aspect_ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_wrap_point() {
+        let a: Rectangle = [10.0, 20.0, 30.0, 40.0];
+        assert_eq!(a.wrap_point([15.0, 25.0]), [15.0, 25.0]);
+        assert_eq!(a.wrap_point([41.0, 25.0]), [11.0, 25.0]);
+        assert_eq!(a.wrap_point([40.0, 60.0]), [10.0, 20.0]);
+        assert_eq!(a.wrap_point([9.0, 19.0]), [39.0, 59.0]);
+        assert_eq!(a.wrap_point([-51.0, 105.0]), [39.0, 25.0]);
+        let b: Rectangle = [40.0, 60.0, -30.0, -40.0];
+        assert_eq!(b.wrap_point([41.0, 25.0]), [11.0, 25.0]);
+        let c: Rectangle = [5.0, 5.0, 0.0, 10.0];
+        assert_eq!(c.wrap_point([7.0, 16.0]), [5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_corners() {
+        let a: Rectangle = [10.0, 20.0, 30.0, 40.0];
+        let c = a.corners();
+        assert_eq!(c, [[10.0, 20.0], [40.0, 20.0], [40.0, 60.0], [10.0, 60.0]]);
+        // Clockwise with the y axis down gives a positive signed area.
+        let area: Scalar = (0..4).map(|i| {
+            let (p, q) = (c[i], c[(i + 1) % 4]);
+            p[0] * q[1] - q[0] * p[1]
+        }).sum();
+        assert_eq!(0.5 * area, 1200.0);
     }
 }
@@ -1,49 +1,249 @@
-use math::{relative_source_rectangle, Scalar};
+use math::{margin_rectangle, relative_source_rectangle, Scalar};
 use types::SourceRectangle;
 
 /// Should be implemented by contexts that
 /// have source rectangle information.
-pub trait SourceRectangled {
+pub trait SourceRectangled: Sized {
+    /// Returns the current source rectangle.
+    fn get_src_rect(&self) -> SourceRectangle;
+
+    /// Replaces the current source rectangle in place.
+    fn set_src_rect(&mut self, rect: SourceRectangle);
+
     /// Adds a source rectangle.
-    fn src_rect(self, x: Scalar, y: Scalar, w: Scalar, h: Scalar) -> Self;
+    ///
+    /// This is the by-value counterpart of `set_src_rect`.
+    /// Coordinates are floating point and are not rounded,
+    /// so fractional values can be used for sub-pixel sampling.
+    #[inline(always)]
+    fn src_rect(mut self, x: Scalar, y: Scalar, w: Scalar, h: Scalar) -> Self {
+        self.set_src_rect([x, y, w, h]);
+        self
+    }
 
     /// Moves to a relative source rectangle using
     /// the current source rectangle as tile.
-    fn src_rel(self, x: Scalar, y: Scalar) -> Self;
+    #[inline(always)]
+    fn src_rel(self, x: Scalar, y: Scalar) -> Self {
+        let r = relative_source_rectangle(self.get_src_rect(), x, y);
+        self.src_rect(r[0], r[1], r[2], r[3])
+    }
 
     /// Flips the source rectangle horizontally.
-    fn src_flip_h(self) -> Self;
+    #[inline(always)]
+    fn src_flip_h(self) -> Self {
+        let r = self.get_src_rect();
+        self.src_rect(r[0] + r[2], r[1], -r[2], r[3])
+    }
 
     /// Flips the source rectangle vertically.
-    fn src_flip_v(self) -> Self;
+    #[inline(always)]
+    fn src_flip_v(self) -> Self {
+        let r = self.get_src_rect();
+        self.src_rect(r[0], r[1] + r[3], r[2], -r[3])
+    }
 
     /// Flips the source rectangle horizontally and vertically.
-    fn src_flip_hv(self) -> Self;
-}
+    #[inline(always)]
+    fn src_flip_hv(self) -> Self {
+        let r = self.get_src_rect();
+        self.src_rect(r[0] + r[2], r[1] + r[3], -r[2], -r[3])
+    }
 
-impl SourceRectangled for SourceRectangle {
+    /// Sets the source rectangle to a tile in a sprite sheet.
+    ///
+    /// Tiles are counted from left to right, then top to bottom,
+    /// with `columns` tiles per row.
+    /// Panics if `columns` is zero.
     #[inline(always)]
-    fn src_rect(self, x: Scalar, y: Scalar, w: Scalar, h: Scalar) -> Self {
-        [x, y, w, h]
+    fn src_tile(self, index: u32, tile_w: Scalar, tile_h: Scalar, columns: u32) -> Self {
+        let col = index % columns;
+        let row = index / columns;
+        self.src_rect(col as Scalar * tile_w, row as Scalar * tile_h, tile_w, tile_h)
     }
 
+    /// Shrinks the source rectangle equally by all sides.
+    ///
+    /// Negative values expand the source rectangle.
+    /// Width and height are clamped to zero, like `Rectangled::margin`,
+    /// so apply margins before flipping.
     #[inline(always)]
-    fn src_rel(self, x: Scalar, y: Scalar) -> Self {
-        relative_source_rectangle(self, x, y)
+    fn src_margin(self, m: Scalar) -> Self {
+        let r = margin_rectangle(self.get_src_rect(), m);
+        self.src_rect(r[0], r[1], r[2], r[3])
     }
 
+    /// Prepares the source rectangle for drawing a quarter turn rotated tile.
+    ///
+    /// A source rectangle is axis aligned, so the sampled pixels can not be rotated by it.
+    /// This swaps the width and height around the center,
+    /// and the image must be rotated 90 degrees with `Transformed::rot_deg` when drawn.
     #[inline(always)]
-    fn src_flip_h(self) -> Self {
-        [self[0] + self[2], self[1], -self[2], self[3]]
+    fn src_rot_90(self) -> Self {
+        let r = self.get_src_rect();
+        let (cx, cy) = (r[0] + 0.5 * r[2], r[1] + 0.5 * r[3]);
+        self.src_rect(cx - 0.5 * r[3], cy - 0.5 * r[2], r[3], r[2])
     }
 
+    /// Rotates the sampled region half a turn.
+    ///
+    /// This is the same as `src_flip_hv`.
     #[inline(always)]
-    fn src_flip_v(self) -> Self {
-        [self[0], self[1] + self[3], self[2], -self[3]]
+    fn src_rot_180(self) -> Self {
+        self.src_flip_hv()
     }
 
+    /// Prepares the source rectangle for drawing a three quarter turn rotated tile.
+    ///
+    /// This is the same as `src_rot_90` followed by `src_rot_180`,
+    /// and the image must be rotated 90 degrees with `Transformed::rot_deg` when drawn.
     #[inline(always)]
-    fn src_flip_hv(self) -> Self {
-        [self[0] + self[2], self[1] + self[3], -self[2], -self[3]]
+    fn src_rot_270(self) -> Self {
+        self.src_rot_90().src_rot_180()
+    }
+
+    /// Returns the source rectangle in texture coordinates from 0 to 1.
+    ///
+    /// The source rectangle is divided by the texture width and height.
+    /// Negative width or height from flipping keeps its sign.
+    #[inline(always)]
+    fn src_uv(&self, tex_w: Scalar, tex_h: Scalar) -> [Scalar; 4] {
+        let r = self.get_src_rect();
+        [r[0] / tex_w, r[1] / tex_h, r[2] / tex_w, r[3] / tex_h]
+    }
+
+    /// Moves by whole tiles within a sprite sheet, wrapping at the edges.
+    ///
+    /// The current source rectangle is used as tile, aligned to a grid from the upper left corner.
+    /// Tiles are counted from left to right, then top to bottom, like `src_tile`,
+    /// so moving past the last column continues on the next row,
+    /// and moving past the last tile continues at the first.
+    /// Leaves the source rectangle unchanged if it is flipped, empty
+    /// or larger than the sheet, so apply flips afterwards.
+    #[inline(always)]
+    fn src_rel_tile(self, dx: i32, dy: i32, sheet_w: Scalar, sheet_h: Scalar) -> Self {
+        let r = self.get_src_rect();
+        let (w, h) = (r[2], r[3]);
+        if w <= 0.0 || h <= 0.0 {
+            return self;
+        }
+        let (columns, rows) = ((sheet_w / w).floor() as i32, (sheet_h / h).floor() as i32);
+        if columns <= 0 || rows <= 0 {
+            return self;
+        }
+        let (col, row) = ((r[0] / w).round() as i32, (r[1] / h).round() as i32);
+        let index = ((row + dy) * columns + col + dx).rem_euclid(columns * rows);
+        self.src_rect((index % columns) as Scalar * w, (index / columns) as Scalar * h, w, h)
+    }
+
+    /// Swaps the width and height of the source rectangle, keeping the origin.
+    ///
+    /// This does not rotate the sampled pixels.
+    /// Use it together with a rotation in the transform for rotated tiles in an atlas,
+    /// or see `src_rot_90` to keep the center fixed instead of the origin.
+    #[inline(always)]
+    fn src_swap_wh(self) -> Self {
+        let r = self.get_src_rect();
+        self.src_rect(r[0], r[1], r[3], r[2])
+    }
+}
+
+impl SourceRectangled for SourceRectangle {
+    #[inline(always)]
+    fn get_src_rect(&self) -> SourceRectangle {
+        *self
+    }
+
+    #[inline(always)]
+    fn set_src_rect(&mut self, rect: SourceRectangle) {
+        *self = rect;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_accessors() {
+        struct Sprite {
+            src: SourceRectangle,
+        }
+
+        impl SourceRectangled for Sprite {
+            fn get_src_rect(&self) -> SourceRectangle {
+                self.src
+            }
+
+            fn set_src_rect(&mut self, rect: SourceRectangle) {
+                self.src = rect;
+            }
+        }
+
+        let a: SourceRectangle = [16.0, 8.0, 16.0, 8.0];
+        let b = Sprite { src: a };
+        assert_eq!(b.src_uv(64.0, 32.0), a.src_uv(64.0, 32.0));
+        assert_eq!(b.src_flip_h().src.src_swap_wh(), a.src_flip_h().src_swap_wh());
+        let b = Sprite { src: a };
+        assert_eq!(b.src_tile(5, 16.0, 8.0, 4).src, [16.0, 8.0, 16.0, 8.0]);
+    }
+
+    #[test]
+    fn test_src_tile() {
+        let a: SourceRectangle = [0.0; 4];
+        assert_eq!(a.src_tile(0, 16.0, 32.0, 4), [0.0, 0.0, 16.0, 32.0]);
+        assert_eq!(a.src_tile(3, 16.0, 32.0, 4), [48.0, 0.0, 16.0, 32.0]);
+        assert_eq!(a.src_tile(4, 16.0, 32.0, 4), [0.0, 32.0, 16.0, 32.0]);
+    }
+
+    #[test]
+    fn test_src_margin() {
+        let a: SourceRectangle = [32.0, 64.0, 32.0, 32.0];
+        assert_eq!(a.src_margin(1.0), [33.0, 65.0, 30.0, 30.0]);
+        assert_eq!(a.src_margin(2.0), [34.0, 66.0, 28.0, 28.0]);
+        assert_eq!(a.src_margin(-1.0), [31.0, 63.0, 34.0, 34.0]);
+        assert_eq!(a.src_margin(20.0), [48.0, 80.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_src_rot() {
+        let a: SourceRectangle = [0.0, 0.0, 32.0, 16.0];
+        assert_eq!(a.src_rot_180(), a.src_flip_hv());
+        assert_eq!(a.src_rot_90(), [8.0, -8.0, 16.0, 32.0]);
+        assert_eq!(a.src_rot_270(), [24.0, 24.0, -16.0, -32.0]);
+    }
+
+    #[test]
+    fn test_src_rect_fractional() {
+        let a: SourceRectangle = [0.0; 4];
+        assert_eq!(a.src_rect(0.5, 1.5, 2.25, 3.75), [0.5, 1.5, 2.25, 3.75]);
+    }
+
+    #[test]
+    fn test_src_uv() {
+        let a: SourceRectangle = [0.0, 0.0, 64.0, 32.0];
+        assert_eq!(a.src_uv(64.0, 32.0), [0.0, 0.0, 1.0, 1.0]);
+        let b: SourceRectangle = [16.0, 8.0, 16.0, 8.0];
+        assert_eq!(b.src_uv(64.0, 32.0), [0.25, 0.25, 0.25, 0.25]);
+        assert_eq!(b.src_flip_hv().src_uv(64.0, 32.0), [0.5, 0.5, -0.25, -0.25]);
+    }
+
+    #[test]
+    fn test_src_rel_tile() {
+        let a: SourceRectangle = [32.0, 0.0, 16.0, 16.0];
+        assert_eq!(a.src_rel_tile(1, 0, 64.0, 32.0), [48.0, 0.0, 16.0, 16.0]);
+        assert_eq!(a.src_rel_tile(2, 0, 64.0, 32.0), [0.0, 16.0, 16.0, 16.0]);
+        assert_eq!(a.src_rel_tile(0, 1, 64.0, 32.0), [32.0, 16.0, 16.0, 16.0]);
+        assert_eq!(a.src_rel_tile(6, 0, 64.0, 32.0), [0.0, 0.0, 16.0, 16.0]);
+        assert_eq!(a.src_rel_tile(-3, 0, 64.0, 32.0), [48.0, 16.0, 16.0, 16.0]);
+        assert_eq!(a.src_rel_tile(1, 0, 8.0, 32.0), a);
+        assert_eq!(a.src_flip_h().src_rel_tile(1, 0, 64.0, 32.0), a.src_flip_h());
+    }
+
+    #[test]
+    fn test_src_swap_wh() {
+        let a: SourceRectangle = [0.0, 0.0, 32.0, 16.0];
+        assert_eq!(a.src_swap_wh(), [0.0, 0.0, 16.0, 32.0]);
+        assert_eq!(a.src_swap_wh().src_swap_wh(), a);
     }
 }
@@ -0,0 +1,86 @@
+//! A stack of transforms for hierarchical rendering.
+
+use math::{identity, multiply, Matrix2d};
+
+/// Stores transforms pushed by nested parts of a scene.
+///
+/// Each entry is the product of all transforms pushed before it,
+/// so reading the current transform does not multiply matrices.
+/// Use `Transformed::with_stack` to apply the current transform to a context.
+#[derive(Debug, Clone, Default)]
+pub struct TransformStack {
+    stack: Vec<Matrix2d>,
+}
+
+impl TransformStack {
+    /// Creates a new empty stack.
+    pub fn new() -> TransformStack {
+        TransformStack { stack: vec![] }
+    }
+
+    /// Appends a transform to the current one and pushes the result.
+    pub fn push(&mut self, transform: Matrix2d) {
+        let m = multiply(self.current(), transform);
+        self.stack.push(m);
+    }
+
+    /// Pops the last pushed transform.
+    ///
+    /// Returns the accumulated transform that was removed,
+    /// or `None` if the stack is empty.
+    pub fn pop(&mut self) -> Option<Matrix2d> {
+        self.stack.pop()
+    }
+
+    /// Returns the product of all pushed transforms.
+    ///
+    /// This is the identity if the stack is empty.
+    pub fn current(&self) -> Matrix2d {
+        match self.stack.last() {
+            None => identity(),
+            Some(&m) => m,
+        }
+    }
+
+    /// Returns the number of pushed transforms.
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns true if no transforms are pushed.
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use math::{rotate_radians, scale, translate};
+    use Transformed;
+
+    #[test]
+    fn test_push_pop() {
+        let mut stack = TransformStack::new();
+        assert_eq!(stack.current(), identity());
+        assert!(stack.is_empty());
+
+        let (a, b, c) = (translate([1.0, 2.0]), rotate_radians(0.5), scale(2.0, 3.0));
+        stack.push(a);
+        stack.push(b);
+        assert_eq!(stack.current(), identity().append_transform(a).append_transform(b));
+        stack.push(c);
+        assert_eq!(stack.len(), 3);
+        assert_eq!(stack.current(), multiply(multiply(a, b), c));
+
+        assert_eq!(stack.pop(), Some(multiply(multiply(a, b), c)));
+        assert_eq!(stack.current(), multiply(a, b));
+        stack.pop();
+        stack.push(c);
+        assert_eq!(stack.current(), multiply(a, c));
+        stack.pop();
+        stack.pop();
+        assert_eq!(stack.pop(), None);
+        assert_eq!(stack.current(), identity());
+    }
+}
@@ -1,17 +1,57 @@
-use Context;
-use math::{multiply, shear, scale, orient, rotate_radians, translate, Matrix2d, Vec2d, Scalar};
+use {Context, Rectangled, TransformStack};
+use math::{bounding_rectangle, compose, decompose, get_rotation, get_scale, get_translation,
+           invert, length, multiply, normalize, shear, scale, orient, rotate_radians, sub,
+           IDENTITY, translate, transform_pos, transform_vec, Matrix2d, Vec2d, Scalar};
 use radians::Radians;
+use types::Rectangle;
+use rectangle::Edge;
+
+/// Whether a transform is appended or prepended.
+///
+/// See `Transformed::apply_transforms`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TransformOp {
+    /// Appends the transform, like `Transformed::append_transform`.
+    Append,
+    /// Prepends the transform, like `Transformed::prepend_transform`.
+    Prepend,
+}
 
 /// Implemented by contexts that can transform.
 pub trait Transformed: Sized {
+    /// Returns the current transform.
+    fn get_transform(&self) -> Matrix2d;
+
+    /// Replaces the current transform in place.
+    fn set_transform(&mut self, transform: Matrix2d);
+
     /// Appends transform to the current one.
-    fn append_transform(self, transform: Matrix2d) -> Self;
+    #[inline(always)]
+    fn append_transform(mut self, transform: Matrix2d) -> Self {
+        let m = multiply(self.get_transform(), transform);
+        self.set_transform(m);
+        self
+    }
 
     /// Prepends transform to the current one.
-    fn prepend_transform(self, transform: Matrix2d) -> Self;
+    #[inline(always)]
+    fn prepend_transform(mut self, transform: Matrix2d) -> Self {
+        let m = multiply(transform, self.get_transform());
+        self.set_transform(m);
+        self
+    }
 
     /// Translate x and y in local coordinates.
-    fn trans(self, x: Scalar, y: Scalar) -> Self;
+    #[inline(always)]
+    fn trans(self, x: Scalar, y: Scalar) -> Self {
+        self.append_transform(translate([x, y]))
+    }
+
+    /// Translate position in local coordinates.
+    #[inline(always)]
+    fn trans_pos(self, pos: Vec2d) -> Self {
+        self.trans(pos[0], pos[1])
+    }
 
     /// Rotates degrees in local coordinates.
     #[inline(always)]
@@ -21,16 +61,48 @@ pub trait Transformed: Sized {
     }
 
     /// Rotate radians in local coordinates.
-    fn rot_rad(self, angle: Scalar) -> Self;
+    #[inline(always)]
+    fn rot_rad(self, angle: Scalar) -> Self {
+        self.append_transform(rotate_radians(angle))
+    }
+
+    /// Rotates turns in local coordinates.
+    ///
+    /// One turn is a full rotation.
+    #[inline(always)]
+    fn rot_turns(self, turns: Scalar) -> Self {
+        let two_pi: Scalar = Radians::_360();
+        self.rot_rad(turns * two_pi)
+    }
 
     /// Orients x axis to look at point locally.
     ///
     /// Leaves x axis unchanged if the point to
     /// look at is the origin.
-    fn orient(self, x: Scalar, y: Scalar) -> Self;
+    #[inline(always)]
+    fn orient(self, x: Scalar, y: Scalar) -> Self {
+        self.append_transform(orient(x, y))
+    }
+
+    /// Orients x axis to look at a target point locally.
+    ///
+    /// Leaves x axis unchanged if the target is the origin.
+    #[inline(always)]
+    fn orient_towards(self, target: Vec2d) -> Self {
+        self.orient(target[0], target[1])
+    }
+
+    /// Scales in local coordinates.
+    #[inline(always)]
+    fn scale(self, sx: Scalar, sy: Scalar) -> Self {
+        self.append_transform(scale(sx, sy))
+    }
 
     /// Scales in local coordinates.
-    fn scale(self, sx: Scalar, sy: Scalar) -> Self;
+    #[inline(always)]
+    fn scale_pos(self, s: Vec2d) -> Self {
+        self.scale(s[0], s[1])
+    }
 
     /// Scales in both directions in local coordinates.
     #[inline(always)]
@@ -57,91 +129,928 @@ pub trait Transformed: Sized {
     }
 
     /// Shears in local coordinates.
-    fn shear(self, v: Vec2d) -> Self;
-}
+    #[inline(always)]
+    fn shear(self, v: Vec2d) -> Self {
+        self.append_transform(shear(v))
+    }
 
-impl Transformed for Matrix2d {
+    /// Skews along the x axis in local coordinates.
     #[inline(always)]
-    fn append_transform(self, transform: Matrix2d) -> Self {
-        multiply(self, transform)
+    fn skew_x(self, amount: Scalar) -> Self {
+        self.shear([amount, 0.0])
     }
 
+    /// Skews along the y axis in local coordinates.
     #[inline(always)]
-    fn prepend_transform(self, transform: Matrix2d) -> Self {
-        multiply(transform, self)
+    fn skew_y(self, amount: Scalar) -> Self {
+        self.shear([0.0, amount])
     }
 
+    /// Slants along the x axis by an angle in degrees, in local coordinates.
+    ///
+    /// The shear factor is the tangent of the angle,
+    /// so angles close to 90 or -90 degrees give extreme shear.
     #[inline(always)]
-    fn trans(self, x: Scalar, y: Scalar) -> Self {
-        let trans = translate([x, y]);
-        multiply(self, trans)
+    fn shear_x_deg(self, angle: Scalar) -> Self {
+        let pi: Scalar = Radians::_180();
+        self.skew_x((angle * pi / 180.0).tan())
     }
 
+    /// Slants along the y axis by an angle in degrees, in local coordinates.
+    ///
+    /// See `shear_x_deg`.
     #[inline(always)]
-    fn rot_rad(self, angle: Scalar) -> Self {
-        let rot = rotate_radians(angle);
-        multiply(self, rot)
+    fn shear_y_deg(self, angle: Scalar) -> Self {
+        let pi: Scalar = Radians::_180();
+        self.skew_y((angle * pi / 180.0).tan())
     }
 
+    /// Rotates degrees around a pivot point in local coordinates.
     #[inline(always)]
-    fn orient(self, x: Scalar, y: Scalar) -> Self {
-        let orient = orient(x, y);
-        multiply(self, orient)
+    fn rot_deg_about(self, angle: Scalar, pivot: Vec2d) -> Self {
+        let pi: Scalar = Radians::_180();
+        self.rot_rad_about(angle * pi / 180.0, pivot)
     }
 
+    /// Rotates radians around a pivot point in local coordinates.
+    ///
+    /// This is the same as translating to the pivot,
+    /// rotating and translating back.
     #[inline(always)]
-    fn scale(self, sx: Scalar, sy: Scalar) -> Self {
-        let scale = scale(sx, sy);
-        multiply(self, scale)
+    fn rot_rad_about(self, angle: Scalar, pivot: Vec2d) -> Self {
+        let m = multiply(multiply(translate(pivot), rotate_radians(angle)),
+                         translate([-pivot[0], -pivot[1]]));
+        self.append_transform(m)
     }
 
+    /// Scales around a pivot point in local coordinates.
+    ///
+    /// The pivot point stays fixed.
     #[inline(always)]
-    fn shear(self, v: Vec2d) -> Self {
-        let shear = shear(v);
-        multiply(self, shear)
+    fn scale_about(self, sx: Scalar, sy: Scalar, pivot: Vec2d) -> Self {
+        let m = multiply(multiply(translate(pivot), scale(sx, sy)),
+                         translate([-pivot[0], -pivot[1]]));
+        self.append_transform(m)
+    }
+
+    /// Scales in both directions around a pivot point in local coordinates.
+    #[inline(always)]
+    fn zoom_about(self, s: Scalar, pivot: Vec2d) -> Self {
+        self.scale_about(s, s, pivot)
+    }
+
+    /// Transforms a point with the current transform, including translation.
+    #[inline(always)]
+    fn transform_point(&self, p: Vec2d) -> Vec2d {
+        transform_pos(self.get_transform(), p)
+    }
+
+    /// Transforms a vector with the current transform, ignoring translation.
+    #[inline(always)]
+    fn transform_vec(&self, v: Vec2d) -> Vec2d {
+        transform_vec(self.get_transform(), v)
+    }
+
+    /// Transforms a point with the inverse of the current transform.
+    ///
+    /// Returns `None` if the current transform is not invertible.
+    #[inline(always)]
+    fn inverse_transform_point(&self, p: Vec2d) -> Option<Vec2d> {
+        invert(self.get_transform()).map(|m| transform_pos(m, p))
+    }
+
+    /// Returns the translation of the current transform.
+    ///
+    /// A `Context` decomposes the transform relative to the view, see `Context::model`,
+    /// so the view is not included.
+    /// This also applies to `get_rotation` and `get_scale`.
+    #[inline(always)]
+    fn get_translation(&self) -> Vec2d {
+        get_translation(self.get_transform())
+    }
+
+    /// Returns the rotation of the current transform in radians.
+    ///
+    /// The transform is decomposed into translation, rotation and scale.
+    /// This is lossy when the transform contains shear.
+    /// See `get_translation` for how a `Context` is decomposed.
+    #[inline(always)]
+    fn get_rotation(&self) -> Scalar {
+        get_rotation(self.get_transform())
+    }
+
+    /// Returns the scale of the current transform.
+    ///
+    /// The transform is decomposed into translation, rotation and scale.
+    /// This is lossy when the transform contains shear.
+    /// See `get_translation` for how a `Context` is decomposed.
+    #[inline(always)]
+    fn get_scale(&self) -> Vec2d {
+        get_scale(self.get_transform())
+    }
+
+    /// Appends transform to the current one in place.
+    #[inline(always)]
+    fn append_transform_mut(&mut self, transform: Matrix2d) -> &mut Self {
+        let m = self.get_transform().append_transform(transform);
+        self.set_transform(m);
+        self
+    }
+
+    /// Prepends transform to the current one in place.
+    #[inline(always)]
+    fn prepend_transform_mut(&mut self, transform: Matrix2d) -> &mut Self {
+        let m = self.get_transform().prepend_transform(transform);
+        self.set_transform(m);
+        self
+    }
+
+    /// Translate x and y in local coordinates in place.
+    #[inline(always)]
+    fn trans_mut(&mut self, x: Scalar, y: Scalar) -> &mut Self {
+        let m = self.get_transform().trans(x, y);
+        self.set_transform(m);
+        self
+    }
+
+    /// Rotate radians in local coordinates in place.
+    #[inline(always)]
+    fn rot_rad_mut(&mut self, angle: Scalar) -> &mut Self {
+        let m = self.get_transform().rot_rad(angle);
+        self.set_transform(m);
+        self
+    }
+
+    /// Scales in local coordinates in place.
+    #[inline(always)]
+    fn scale_mut(&mut self, sx: Scalar, sy: Scalar) -> &mut Self {
+        let m = self.get_transform().scale(sx, sy);
+        self.set_transform(m);
+        self
+    }
+
+    /// Removes rotation from the current transform,
+    /// keeping translation and scale.
+    ///
+    /// The transform is decomposed into translation, rotation and scale.
+    /// This is lossy when the transform contains shear.
+    /// A flip is kept as a negative scale, see `math::decompose`,
+    /// on the axis that is closest to the flipped axis before the rotation is removed,
+    /// so `flip_h` and `flip_v` are left unchanged.
+    /// A `Context` keeps the view and removes rotation from `Context::model`.
+    #[inline(always)]
+    fn reset_rotation(mut self) -> Self {
+        let (t, r, s) = decompose(self.get_transform());
+        let s = if s[1] < 0.0 && r.cos() < 0.0 { [-s[0], -s[1]] } else { s };
+        self.set_transform(compose(t, 0.0, s));
+        self
+    }
+
+    /// Removes scale from the current transform,
+    /// keeping translation and rotation.
+    ///
+    /// The transform is decomposed into translation, rotation and scale.
+    /// This is lossy when the transform contains shear.
+    /// A flip is a negative scale and is removed too.
+    /// A `Context` keeps the view and removes scale from `Context::model`.
+    #[inline(always)]
+    fn reset_scale(mut self) -> Self {
+        let t = self.get_translation();
+        let r = self.get_rotation();
+        self.set_transform(multiply(translate(t), rotate_radians(r)));
+        self
+    }
+
+    /// Interpolates from the current transform to a target transform.
+    ///
+    /// Both transforms are decomposed into translation, rotation and scale,
+    /// which are interpolated separately.
+    /// Rotation takes the shortest path around the circle.
+    /// A flip is kept as a negative scale, see `math::decompose`.
+    /// This is lossy when a transform contains shear,
+    /// or when only one of the transforms flips.
+    /// A `Context` interpolates `Context::model` and keeps the view.
+    ///
+    /// 0 returns the current transform and 1 returns the target exactly.
+    #[inline(always)]
+    fn lerp_transform(mut self, target: Matrix2d, t: Scalar) -> Self {
+        if t == 0.0 {
+            return self;
+        }
+        if t == 1.0 {
+            self.set_transform(target);
+            return self;
+        }
+
+        let pi: Scalar = Radians::_180();
+        let two_pi: Scalar = Radians::_360();
+        let (t0, r0, s0) = decompose(self.get_transform());
+        let (t1, r1, s1) = decompose(target);
+        let mut dr = (r1 - r0) % two_pi;
+        if dr > pi {
+            dr -= two_pi;
+        } else if dr < -pi {
+            dr += two_pi;
+        }
+        let u = 1.0 - t;
+        let m = compose([t0[0] * u + t1[0] * t, t0[1] * u + t1[1] * t],
+                        r0 + dr * t,
+                        [s0[0] * u + s1[0] * t, s0[1] * u + s1[1] * t]);
+        self.set_transform(m);
+        self
+    }
+
+    /// Mirrors across a line in local coordinates.
+    ///
+    /// The line goes through a point along a direction.
+    /// Leaves the transform unchanged if the direction is zero.
+    #[inline(always)]
+    fn mirror(self, point: Vec2d, direction: Vec2d) -> Self {
+        let (dx, dy) = (direction[0], direction[1]);
+        let len = dx * dx + dy * dy;
+        if len == 0.0 {
+            return self;
+        }
+
+        let (a, b) = ((dx * dx - dy * dy) / len, 2.0 * dx * dy / len);
+        let reflect = [[a, b, 0.0], [b, -a, 0.0]];
+        let m = multiply(multiply(translate(point), reflect),
+                         translate([-point[0], -point[1]]));
+        self.append_transform(m)
+    }
+
+    /// Replaces the current transform, ignoring the previous one.
+    #[inline(always)]
+    fn set_absolute_transform(mut self, transform: Matrix2d) -> Self {
+        self.set_transform(transform);
+        self
+    }
+
+    /// Appends transforms to the current one, in order.
+    ///
+    /// The transforms are multiplied together first
+    /// and appended as a single transform.
+    #[inline(always)]
+    fn append_transforms(self, transforms: &[Matrix2d]) -> Self {
+        match transforms.split_first() {
+            None => self,
+            Some((&first, rest)) => {
+                self.append_transform(rest.iter().fold(first, |acc, &m| multiply(acc, m)))
+            }
+        }
+    }
+
+    /// Returns the axis-aligned bounds of a transformed rectangle.
+    ///
+    /// All four corners are transformed, so the bounds grow under rotation.
+    #[inline(always)]
+    fn transform_rectangle_bounds(&self, rect: [Scalar; 4]) -> [Scalar; 4] {
+        let m = self.get_transform();
+        let c = rect.corners();
+        bounding_rectangle(&[transform_pos(m, c[0]),
+                             transform_pos(m, c[1]),
+                             transform_pos(m, c[2]),
+                             transform_pos(m, c[3])])
+            .unwrap()
+    }
+
+    /// Returns true if the transform is the identity within `epsilon`.
+    #[inline(always)]
+    fn is_identity(&self, epsilon: Scalar) -> bool {
+        let m = self.get_transform();
+        m.iter().zip(IDENTITY.iter()).all(|(a, b)| {
+            a.iter().zip(b.iter()).all(|(&x, &y)| (x - y).abs() <= epsilon)
+        })
+    }
+
+    /// Returns the determinant of the linear part of the current transform.
+    ///
+    /// The absolute value is the area scale factor.
+    #[inline(always)]
+    fn get_determinant(&self) -> Scalar {
+        let m = self.get_transform();
+        m[0][0] * m[1][1] - m[0][1] * m[1][0]
+    }
+
+    /// Returns true if the current transform reverses triangle winding.
+    ///
+    /// This happens when the determinant is negative,
+    /// for example after an odd number of flips.
+    #[inline(always)]
+    fn flips_orientation(&self) -> bool {
+        self.get_determinant() < 0.0
+    }
+
+    /// Rotates radians rigidly around the local origin.
+    ///
+    /// Unlike `rot_rad`, which rotates in local coordinates that might already be scaled,
+    /// this rotates the transformed result as a whole.
+    /// Shapes keep the proportions they already have,
+    /// so a rectangle under non-uniform scale stays rectangular instead of being skewed.
+    /// A `Context` rotates in view coordinates, see `Context::model`.
+    #[inline(always)]
+    fn rot_rad_rigid(self, angle: Scalar) -> Self {
+        let t = self.get_translation();
+        let m = multiply(multiply(translate(t), rotate_radians(angle)),
+                         translate([-t[0], -t[1]]));
+        self.prepend_transform(m)
+    }
+
+    /// Appends the current transform of a transform stack.
+    #[inline(always)]
+    fn with_stack(self, stack: &TransformStack) -> Self {
+        self.append_transform(stack.current())
+    }
+
+    /// Rotates degrees around the center of a rectangle in local coordinates.
+    ///
+    /// This is useful for rotating a sprite around its own center.
+    #[inline(always)]
+    fn rot_deg_about_center(self, angle: Scalar, rect: Rectangle) -> Self {
+        self.rot_deg_about(angle, rect.center())
+    }
+
+    /// Rounds the translation of the current transform to whole pixels.
+    ///
+    /// See `snap_to_grid`.
+    #[inline(always)]
+    fn snap_to_pixel(self) -> Self {
+        self.snap_to_grid(1.0)
+    }
+
+    /// Rounds the translation of the current transform to a multiple of a grid size.
+    ///
+    /// Leaves rotation, scale and shear unchanged.
+    /// For a matrix the translation is rounded directly,
+    /// while a `Context` rounds it in view coordinates,
+    /// which are pixels for a context created with `new_abs` or `new_viewport`.
+    /// Leaves the transform unchanged if the grid size is not positive.
+    #[inline(always)]
+    fn snap_to_grid(mut self, size: Scalar) -> Self {
+        if size > 0.0 {
+            let mut m = self.get_transform();
+            m[0][2] = (m[0][2] / size).round() * size;
+            m[1][2] = (m[1][2] / size).round() * size;
+            self.set_transform(m);
+        }
+        self
+    }
+
+    /// Replaces the current transform with translation, rotation in radians and scale.
+    ///
+    /// See `math::compose` for the order they are applied.
+    /// A `Context` keeps the view and replaces `Context::model`,
+    /// so the values are in view coordinates.
+    #[inline(always)]
+    fn set_trs(mut self, translation: Vec2d, rotation: Scalar, scale: Vec2d) -> Self {
+        self.set_transform(compose(translation, rotation, scale));
+        self
+    }
+
+    /// Mirrors across an edge of a rectangle in local coordinates.
+    ///
+    /// The rectangle is normalized first, so `Edge::Left` is the edge with the smallest x.
+    /// Drawing the same rectangle with the result places the reflection
+    /// next to the original, sharing the edge.
+    #[inline(always)]
+    fn flip_about_edge(self, edge: Edge, rect: Rectangle) -> Self {
+        let r = rect.normalize();
+        let (point, direction) = match edge {
+            Edge::Top => ([r[0], r[1]], [1.0, 0.0]),
+            Edge::Bottom => ([r[0], r[1] + r[3]], [1.0, 0.0]),
+            Edge::Left => ([r[0], r[1]], [0.0, 1.0]),
+            Edge::Right => ([r[0] + r[2], r[1]], [0.0, 1.0]),
+        };
+        self.mirror(point, direction)
+    }
+
+    /// Appends or prepends transforms to the current one, in order.
+    ///
+    /// The transforms are multiplied together with the current transform first,
+    /// and the result is set once.
+    #[inline(always)]
+    fn apply_transforms(mut self, ops: &[(TransformOp, Matrix2d)]) -> Self {
+        let m = ops.iter().fold(self.get_transform(), |acc, &(op, m)| match op {
+            TransformOp::Append => multiply(acc, m),
+            TransformOp::Prepend => multiply(m, acc),
+        });
+        self.set_transform(m);
+        self
+    }
+
+    /// Returns the distance between the translations of two transforms.
+    ///
+    /// The translations come from `get_translation`,
+    /// so a `Context` measures the distance in view coordinates, see `Context::model`.
+    #[inline(always)]
+    fn distance_to(&self, other: &Self) -> Scalar {
+        length(sub(other.get_translation(), self.get_translation()))
+    }
+
+    /// Returns the direction from the translation of this transform to another,
+    /// with length one.
+    ///
+    /// Returns `None` if the translations are the same.
+    /// See `distance_to`.
+    #[inline(always)]
+    fn direction_to(&self, other: &Self) -> Option<Vec2d> {
+        normalize(sub(other.get_translation(), self.get_translation()))
+    }
+}
+
+impl Transformed for Matrix2d {
+    #[inline(always)]
+    fn get_transform(&self) -> Matrix2d {
+        *self
+    }
+
+    #[inline(always)]
+    fn set_transform(&mut self, transform: Matrix2d) {
+        *self = transform;
     }
 }
 
 impl Transformed for Context {
     #[inline(always)]
-    fn append_transform(mut self, transform: Matrix2d) -> Self {
-        self.transform = self.transform.append_transform(transform);
-        self
+    fn get_transform(&self) -> Matrix2d {
+        self.transform
     }
 
     #[inline(always)]
-    fn prepend_transform(mut self, transform: Matrix2d) -> Self {
-        self.transform = self.transform.prepend_transform(transform);
+    fn set_transform(&mut self, transform: Matrix2d) {
+        self.transform = transform;
+    }
+
+    #[inline(always)]
+    fn get_translation(&self) -> Vec2d {
+        get_translation(self.model().unwrap_or(self.transform))
+    }
+
+    #[inline(always)]
+    fn get_rotation(&self) -> Scalar {
+        get_rotation(self.model().unwrap_or(self.transform))
+    }
+
+    #[inline(always)]
+    fn get_scale(&self) -> Vec2d {
+        get_scale(self.model().unwrap_or(self.transform))
+    }
+
+    #[inline(always)]
+    fn reset_rotation(mut self) -> Self {
+        if let Some(model) = self.model() {
+            self.transform = multiply(self.view, model.reset_rotation());
+        }
         self
     }
 
     #[inline(always)]
-    fn trans(mut self, x: Scalar, y: Scalar) -> Self {
-        self.transform = self.transform.trans(x, y);
+    fn reset_scale(mut self) -> Self {
+        if let Some(model) = self.model() {
+            self.transform = multiply(self.view, model.reset_scale());
+        }
         self
     }
 
     #[inline(always)]
-    fn rot_rad(mut self, angle: Scalar) -> Self {
-        self.transform = self.transform.rot_rad(angle);
+    fn lerp_transform(mut self, target: Matrix2d, t: Scalar) -> Self {
+        if t == 0.0 {
+            return self;
+        }
+        if t == 1.0 {
+            self.transform = target;
+            return self;
+        }
+
+        self.transform = match invert(self.view) {
+            Some(inv) => {
+                let (model, target) = (multiply(inv, self.transform), multiply(inv, target));
+                multiply(self.view, model.lerp_transform(target, t))
+            }
+            None => self.transform.lerp_transform(target, t),
+        };
         self
     }
 
     #[inline(always)]
-    fn orient(mut self, x: Scalar, y: Scalar) -> Self {
-        self.transform = self.transform.orient(x, y);
+    fn rot_rad_rigid(mut self, angle: Scalar) -> Self {
+        if let Some(model) = self.model() {
+            self.transform = multiply(self.view, model.rot_rad_rigid(angle));
+        }
         self
     }
 
     #[inline(always)]
-    fn scale(mut self, sx: Scalar, sy: Scalar) -> Self {
-        self.transform = self.transform.scale(sx, sy);
+    fn set_trs(mut self, translation: Vec2d, rotation: Scalar, scale: Vec2d) -> Self {
+        self.transform = multiply(self.view, compose(translation, rotation, scale));
         self
     }
 
     #[inline(always)]
-    fn shear(mut self, v: Vec2d) -> Self {
-        self.transform = self.transform.shear(v);
+    fn snap_to_grid(mut self, size: Scalar) -> Self {
+        if let Some(model) = self.model() {
+            self.transform = multiply(self.view, model.snap_to_grid(size));
+        }
         self
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use math::identity;
+
+    fn assert_near(a: Matrix2d, b: Matrix2d) {
+        for i in 0..2 {
+            for j in 0..3 {
+                assert!((a[i][j] - b[i][j]).abs() < 0.00001, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_accessors() {
+        struct Node {
+            transform: Matrix2d,
+        }
+
+        impl Transformed for Node {
+            fn get_transform(&self) -> Matrix2d {
+                self.transform
+            }
+
+            fn set_transform(&mut self, transform: Matrix2d) {
+                self.transform = transform;
+            }
+        }
+
+        let m: Matrix2d = identity();
+        let a = Node { transform: m }.trans(10.0, 20.0).rot_rad(0.5).scale(2.0, 3.0);
+        assert_eq!(a.transform, m.trans(10.0, 20.0).rot_rad(0.5).scale(2.0, 3.0));
+        let b = Node { transform: m }.shear([0.5, 0.0]).orient(1.0, 1.0);
+        assert_eq!(b.prepend_transform(a.transform).transform,
+                   m.shear([0.5, 0.0]).orient(1.0, 1.0).prepend_transform(a.transform));
+    }
+
+    #[test]
+    fn test_rot_rad_about() {
+        let m: Matrix2d = identity();
+        let pivot = [10.0, 20.0];
+        let a = m.rot_deg_about(90.0, pivot);
+        let p = transform_pos(a, pivot);
+        assert!((p[0] - pivot[0]).abs() < 0.00001);
+        assert!((p[1] - pivot[1]).abs() < 0.00001);
+        assert_near(a, m.trans(10.0, 20.0).rot_deg(90.0).trans(-10.0, -20.0));
+    }
+
+    #[test]
+    fn test_zoom_about() {
+        let m: Matrix2d = identity();
+        let pivot = [10.0, 20.0];
+        let a = m.zoom_about(2.0, pivot);
+        assert_eq!(transform_pos(a, pivot), pivot);
+        assert_eq!(transform_pos(a, [0.0, 0.0]), [-10.0, -20.0]);
+        assert_near(m.scale_about(2.0, 3.0, pivot),
+                    m.trans(10.0, 20.0).scale(2.0, 3.0).trans(-10.0, -20.0));
+    }
+
+    #[test]
+    fn test_transform_point() {
+        use Context;
+
+        let c = Context::new().trans(10.0, 20.0).rot_deg(90.0);
+        let a = c.transform_point([0.0, 0.0]);
+        assert!((a[0] - 10.0).abs() < 0.00001 && (a[1] - 20.0).abs() < 0.00001);
+        let b = c.transform_point([1.0, 0.0]);
+        assert!((b[0] - 10.0).abs() < 0.00001 && (b[1] - 21.0).abs() < 0.00001);
+        let v = c.transform_vec([1.0, 0.0]);
+        assert!(v[0].abs() < 0.00001 && (v[1] - 1.0).abs() < 0.00001);
+        let u = c.transform_vec([0.0, 1.0]);
+        assert!((u[0] + 1.0).abs() < 0.00001 && u[1].abs() < 0.00001);
+    }
+
+    #[test]
+    fn test_inverse_transform_point() {
+        let m: Matrix2d = identity();
+        let a = m.trans(10.0, 20.0).rot_deg(30.0).scale(2.0, 3.0);
+        let p = [5.0, -7.0];
+        let q = a.inverse_transform_point(a.transform_point(p)).unwrap();
+        assert!((q[0] - p[0]).abs() < 0.00001 && (q[1] - p[1]).abs() < 0.00001);
+        assert_eq!(m.scale(0.0, 1.0).inverse_transform_point(p), None);
+        let near_singular = [[1.0, 2.0, 0.0], [0.5, 1.0 + 1e-16, 0.0]];
+        assert_eq!(near_singular.inverse_transform_point(p), None);
+
+        let tiny = m.trans(1e-9, 2e-9).scale(1e-9, 1e-9);
+        let q = tiny.inverse_transform_point(tiny.transform_point(p)).unwrap();
+        assert!((q[0] - p[0]).abs() < 0.001 && (q[1] - p[1]).abs() < 0.001);
+        let c = Context::new_abs(640.0, 480.0).zoom_view_at(1e-6, [0.0, 0.0]);
+        assert!(c.model().is_some() && c.view_rectangle().is_some());
+    }
+
+    #[test]
+    fn test_trans_pos_scale_pos() {
+        let m: Matrix2d = identity();
+        assert_eq!(m.trans_pos([3.0, 4.0]), m.trans(3.0, 4.0));
+        assert_eq!(m.scale_pos([3.0, 4.0]), m.scale(3.0, 4.0));
+    }
+
+    #[test]
+    fn test_decompose() {
+        let m: Matrix2d = identity();
+        let a = m.trans(10.0, 20.0).rot_rad(0.5).scale(2.0, 3.0);
+        assert_eq!(a.get_translation(), [10.0, 20.0]);
+        assert!((a.get_rotation() - 0.5).abs() < 0.00001);
+        let s = a.get_scale();
+        assert!((s[0] - 2.0).abs() < 0.00001 && (s[1] - 3.0).abs() < 0.00001);
+
+        let c = Context::new_abs(640.0, 480.0);
+        let (t, s) = (c.get_translation(), c.get_scale());
+        assert!(t[0].abs() < 0.001 && t[1].abs() < 0.001);
+        assert!(c.get_rotation().abs() < 0.0001);
+        assert!((s[0] - 1.0).abs() < 0.0001 && (s[1] - 1.0).abs() < 0.0001);
+        let d = c.trans(10.0, 20.0).rot_rad(0.5).scale(2.0, 3.0);
+        let t = d.get_translation();
+        assert!((t[0] - 10.0).abs() < 0.001 && (t[1] - 20.0).abs() < 0.001);
+        assert!((d.get_rotation() - 0.5).abs() < 0.0001);
+        let s = d.get_scale();
+        assert!((s[0] - 2.0).abs() < 0.0001 && (s[1] - 3.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_mut() {
+        use Context;
+
+        let c = Context::new();
+        let mut d = c;
+        d.trans_mut(10.0, 20.0).rot_rad_mut(0.5).scale_mut(2.0, 3.0);
+        assert_eq!(d.transform, c.trans(10.0, 20.0).rot_rad(0.5).scale(2.0, 3.0).transform);
+        let m = scale(2.0, 2.0);
+        d.append_transform_mut(m).prepend_transform_mut(m);
+        assert_eq!(d.transform,
+                   c.trans(10.0, 20.0).rot_rad(0.5).scale(2.0, 3.0)
+                    .append_transform(m).prepend_transform(m).transform);
+    }
+
+    #[test]
+    fn test_reset_rotation_scale() {
+        let m: Matrix2d = identity();
+        assert_near(m.trans(10.0, 20.0).rot_deg(30.0).reset_rotation(), m.trans(10.0, 20.0));
+        let a = m.trans(10.0, 20.0).rot_deg(30.0).scale(2.0, 3.0);
+        assert_near(a.reset_rotation(), m.trans(10.0, 20.0).scale(2.0, 3.0));
+        assert_near(a.reset_scale(), m.trans(10.0, 20.0).rot_deg(30.0));
+        assert_near(m.trans(5.0, 0.0).flip_v().reset_rotation(), m.trans(5.0, 0.0).flip_v());
+        assert_near(m.trans(5.0, 0.0).flip_h().reset_rotation(), m.trans(5.0, 0.0).flip_h());
+        assert_near(m.rot_deg(30.0).flip_h().scale(2.0, 3.0).reset_rotation(),
+                    m.flip_h().scale(2.0, 3.0));
+        assert_near(m.rot_deg(150.0).flip_v().reset_rotation(), m.flip_h());
+
+        let c = Context::new_abs(640.0, 480.0);
+        assert_near(c.reset_rotation().transform, c.transform);
+        assert_near(c.reset_scale().transform, c.transform);
+        let d = c.trans(10.0, 20.0).rot_deg(30.0).scale(2.0, 3.0);
+        assert_near(d.reset_rotation().transform, c.trans(10.0, 20.0).scale(2.0, 3.0).transform);
+        assert_near(d.reset_scale().transform, c.trans(10.0, 20.0).rot_deg(30.0).transform);
+        assert_near(c.rot_deg(30.0).flip_v().reset_rotation().transform, c.flip_v().transform);
+    }
+
+    #[test]
+    fn test_skew() {
+        let m: Matrix2d = identity();
+        assert_eq!(m.skew_x(0.5), m.shear([0.5, 0.0]));
+        assert_eq!(m.skew_y(0.5), m.shear([0.0, 0.5]));
+    }
+
+    #[test]
+    fn test_lerp_transform() {
+        let m: Matrix2d = identity();
+        let a = m.trans(10.0, 0.0);
+        let b = m.trans(20.0, 10.0).rot_deg(90.0).zoom(3.0);
+        assert_eq!(a.lerp_transform(b, 0.0), a);
+        assert_eq!(a.lerp_transform(b, 1.0), b);
+        assert_near(a.lerp_transform(b, 0.5), m.trans(15.0, 5.0).rot_deg(45.0).zoom(2.0));
+
+        let c = m.rot_deg(170.0);
+        let d = m.rot_deg(-170.0);
+        assert_near(c.lerp_transform(d, 0.5), m.rot_deg(180.0));
+
+        let e = m.trans(10.0, 0.0).flip_v();
+        let f = m.trans(20.0, 10.0).flip_v().zoom(3.0);
+        assert_near(e.lerp_transform(f, 0.5), m.trans(15.0, 5.0).flip_v().zoom(2.0));
+
+        let g = Context::new_abs(640.0, 480.0);
+        assert_near(g.lerp_transform(g.transform, 0.5).transform, g.transform);
+        let h = g.trans(20.0, 10.0).rot_deg(90.0).zoom(3.0);
+        assert_eq!(g.lerp_transform(h.transform, 1.0).transform, h.transform);
+        assert_near(g.trans(10.0, 0.0).lerp_transform(h.transform, 0.5).transform,
+                    g.trans(15.0, 5.0).rot_deg(45.0).zoom(2.0).transform);
+    }
+
+    #[test]
+    fn test_mirror() {
+        let m: Matrix2d = identity();
+        assert_near(m.mirror([0.0, 0.0], [1.0, 0.0]), m.flip_v());
+        assert_near(m.mirror([0.0, 0.0], [0.0, 2.0]), m.flip_h());
+        assert_eq!(m.mirror([1.0, 1.0], [0.0, 0.0]), m);
+        let a = m.mirror([1.0, 0.0], [1.0, 1.0]);
+        let p = a.transform_point([1.0, 1.0]);
+        assert!((p[0] - 2.0).abs() < 0.00001 && p[1].abs() < 0.00001);
+    }
+
+    #[test]
+    fn test_set_absolute_transform() {
+        use Context;
+
+        let m = scale(2.0, 3.0);
+        let c = Context::new().trans(10.0, 20.0).rot_deg(30.0).set_absolute_transform(m);
+        assert_eq!(c.transform, m);
+    }
+
+    #[test]
+    fn test_rot_turns() {
+        let m: Matrix2d = identity();
+        assert_near(m.rot_turns(0.25), m.rot_deg(90.0));
+        assert_near(m.rot_turns(1.0), m);
+    }
+
+    #[test]
+    fn test_append_transforms() {
+        let m: Matrix2d = identity::<Scalar>().trans(1.0, 2.0);
+        let (a, b, c) = (rotate_radians(0.5), scale(2.0, 3.0), translate([4.0, 5.0]));
+        assert_near(m.append_transforms(&[a, b, c]),
+                    m.append_transform(a).append_transform(b).append_transform(c));
+        assert_eq!(m.append_transforms(&[]), m);
+    }
+
+    #[test]
+    fn test_transform_rectangle_bounds() {
+        let m: Matrix2d = identity::<Scalar>().trans(10.0, 20.0).scale(2.0, 3.0);
+        assert_eq!(m.transform_rectangle_bounds([1.0, 1.0, 2.0, 2.0]),
+                   [12.0, 23.0, 4.0, 6.0]);
+
+        let m: Matrix2d = identity::<Scalar>().rot_deg(45.0);
+        let b = m.transform_rectangle_bounds([0.0, 0.0, 1.0, 1.0]);
+        let s = 0.5f64.sqrt() as Scalar;
+        let expected = [-s, 0.0, 2.0 * s, 2.0 * s];
+        for i in 0..4 {
+            assert!((b[i] - expected[i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_is_identity() {
+        assert!(IDENTITY.is_identity(0.0));
+        assert_eq!(IDENTITY, identity());
+
+        let near: Matrix2d = identity::<Scalar>().trans(0.0001, 0.0).rot_rad(0.0001);
+        assert!(!near.is_identity(0.0));
+        assert!(near.is_identity(0.001));
+
+        let m: Matrix2d = identity::<Scalar>().trans(1.0, 0.0);
+        assert!(!m.is_identity(0.001));
+        assert!(!identity::<Scalar>().zoom(2.0).is_identity(0.001));
+    }
+
+    #[test]
+    fn test_orient_towards() {
+        let m: Matrix2d = identity::<Scalar>().trans(1.0, 2.0);
+        assert_eq!(m.orient_towards([3.0, 4.0]), m.orient(3.0, 4.0));
+        assert_eq!(m.orient_towards([0.0, 0.0]), m);
+    }
+
+    #[test]
+    fn test_determinant() {
+        let m: Matrix2d = identity::<Scalar>().trans(5.0, 6.0).rot_rad(0.7);
+        assert!((m.get_determinant() - 1.0).abs() < 1e-5);
+        assert_eq!(identity::<Scalar>().scale(2.0, 3.0).get_determinant(), 6.0);
+        assert!(!m.flips_orientation());
+        assert!(m.flip_h().flips_orientation());
+        assert!(m.flip_v().flips_orientation());
+        assert!(!m.flip_hv().flips_orientation());
+    }
+
+    #[test]
+    fn test_rot_rad_rigid() {
+        let pi: Scalar = Radians::_180();
+        let m: Matrix2d = identity::<Scalar>().trans(5.0, 5.0).scale(2.0, 1.0);
+        let rigid = m.rot_rad_rigid(0.5 * pi);
+        let local = m.rot_rad(0.5 * pi);
+        let (a, b) = (rigid.transform_point([1.0, 0.0]), local.transform_point([1.0, 0.0]));
+        assert!((a[0] - 5.0).abs() < 1e-5 && (a[1] - 7.0).abs() < 1e-5);
+        assert!((b[0] - 5.0).abs() < 1e-5 && (b[1] - 6.0).abs() < 1e-5);
+
+        let r = m.rot_rad_rigid(0.3);
+        for &v in &[[1.0, 0.0], [0.0, 1.0], [1.0, 1.0]] {
+            let (p, q) = (m.transform_vec(v), r.transform_vec(v));
+            assert!((p[0].hypot(p[1]) - q[0].hypot(q[1])).abs() < 1e-5);
+        }
+
+        let c = Context::new_abs(640.0, 480.0).trans(100.0, 100.0).rot_rad_rigid(0.5 * pi);
+        let model = c.model().unwrap();
+        let expected = [[0.0, -1.0, 100.0], [1.0, 0.0, 100.0]];
+        for i in 0..2 {
+            for j in 0..3 {
+                assert!((model[i][j] - expected[i][j]).abs() < 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_stack() {
+        let mut stack = TransformStack::new();
+        stack.push(translate([1.0, 2.0]));
+        stack.push(scale(2.0, 2.0));
+        let c = Context::new_abs(640.0, 480.0);
+        assert_eq!(c.with_stack(&stack).transform, c.trans(1.0, 2.0).zoom(2.0).transform);
+    }
+
+    #[test]
+    fn test_rot_deg_about_center() {
+        let rect = [10.0, 20.0, 30.0, 30.0];
+        let m: Matrix2d = identity::<Scalar>().trans(5.0, 5.0);
+        let r = m.rot_deg_about_center(37.0, rect);
+        let (a, b) = (r.transform_point([25.0, 35.0]), m.transform_point([25.0, 35.0]));
+        assert!((a[0] - b[0]).abs() < 1e-5 && (a[1] - b[1]).abs() < 1e-5);
+        assert_near(r, m.rot_deg_about(37.0, [25.0, 35.0]));
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        let m: Matrix2d = identity::<Scalar>().trans(10.4, -3.6).zoom(2.0);
+        assert_eq!(m.snap_to_pixel(), identity::<Scalar>().trans(10.0, -4.0).zoom(2.0));
+        let n: Matrix2d = identity::<Scalar>().trans(10.0, -4.0).rot_rad(0.5);
+        assert_eq!(n.snap_to_pixel(), n);
+        assert_eq!(m.snap_to_grid(8.0), identity::<Scalar>().trans(8.0, 0.0).zoom(2.0));
+        assert_eq!(m.snap_to_grid(0.0), m);
+
+        let c = Context::new_abs(640.0, 480.0);
+        let d = c.trans(10.4, 20.6).rot_deg(45.0).snap_to_pixel();
+        assert_near(d.transform, c.trans(10.0, 21.0).rot_deg(45.0).transform);
+    }
+
+    #[test]
+    fn test_set_trs() {
+        let m: Matrix2d = identity::<Scalar>().trans(1.0, 2.0);
+        let n = m.set_trs([10.0, 20.0], 0.5, [2.0, 3.0]);
+        assert_near(n, identity().trans(10.0, 20.0).rot_rad(0.5).scale(2.0, 3.0));
+        let c = Context::new_abs(640.0, 480.0);
+        let d = c.rot_deg(30.0).set_trs([10.0, 20.0], 0.5, [2.0, 3.0]);
+        assert_near(d.transform, c.trans(10.0, 20.0).rot_rad(0.5).scale(2.0, 3.0).transform);
+        assert_eq!(d.view, c.view);
+    }
+
+    #[test]
+    fn test_flip_about_edge() {
+        let rect = [10.0, 20.0, 30.0, 40.0];
+        let m: Matrix2d = identity();
+        let left = m.flip_about_edge(Edge::Left, rect);
+        assert_eq!(left.transform_rectangle_bounds(rect), [-20.0, 20.0, 30.0, 40.0]);
+        assert_eq!(left.transform_point([10.0, 30.0]), [10.0, 30.0]);
+        assert_eq!(left.transform_point([15.0, 30.0]), [5.0, 30.0]);
+        let right = m.flip_about_edge(Edge::Right, rect);
+        assert_eq!(right.transform_rectangle_bounds(rect), [40.0, 20.0, 30.0, 40.0]);
+        let top = m.flip_about_edge(Edge::Top, rect);
+        assert_eq!(top.transform_rectangle_bounds(rect), [10.0, -20.0, 30.0, 40.0]);
+        let bottom = m.flip_about_edge(Edge::Bottom, [10.0, 60.0, 30.0, -40.0]);
+        assert_eq!(bottom.transform_rectangle_bounds(rect), [10.0, 60.0, 30.0, 40.0]);
+        assert!(bottom.flips_orientation());
+    }
+
+    #[test]
+    fn test_apply_transforms() {
+        let m: Matrix2d = identity::<Scalar>().trans(1.0, 2.0);
+        let (a, b, c) = (rotate_radians(0.5), scale(2.0, 3.0), translate([4.0, 5.0]));
+        let ops = [(TransformOp::Append, a), (TransformOp::Prepend, b), (TransformOp::Append, c)];
+        assert_near(m.apply_transforms(&ops),
+                    m.append_transform(a).prepend_transform(b).append_transform(c));
+        assert_near(m.apply_transforms(&ops), multiply(multiply(b, multiply(m, a)), c));
+        assert_eq!(m.apply_transforms(&[]), m);
+    }
+
+    #[test]
+    fn test_distance_to() {
+        let a: Matrix2d = identity::<Scalar>().trans(1.0, 2.0).rot_rad(0.5);
+        let b: Matrix2d = identity::<Scalar>().trans(4.0, 6.0).zoom(3.0);
+        assert_eq!(a.distance_to(&b), 5.0);
+        assert_eq!(b.distance_to(&a), 5.0);
+        assert_eq!(a.direction_to(&b), Some([0.6, 0.8]));
+        assert_eq!(a.distance_to(&a), 0.0);
+        assert_eq!(a.direction_to(&a), None);
+
+        let c = Context::new_abs(640.0, 480.0);
+        let (x, y) = (c.trans(100.0, 0.0), c.trans(0.0, 100.0));
+        assert!((c.distance_to(&x) - 100.0).abs() < 0.001);
+        assert!((c.distance_to(&y) - 100.0).abs() < 0.001);
+        let d = c.trans(30.0, 40.0).rot_rad(0.5).direction_to(&c.trans(60.0, 80.0)).unwrap();
+        assert!((d[0] - 0.6).abs() < 0.0001 && (d[1] - 0.8).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_shear_deg() {
+        let m: Matrix2d = identity();
+        assert_near(m.shear_x_deg(45.0), m.skew_x(1.0));
+        assert_near(m.shear_y_deg(-45.0), m.skew_y(-1.0));
+        assert!((m.shear_x_deg(45.0)[0][1] - 1.0).abs() < 1e-5);
+        assert_eq!(m.shear_x_deg(0.0), m);
+        assert!(m.shear_y_deg(89.9)[1][0] > 500.0);
+    }
+}